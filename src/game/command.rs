@@ -1,7 +1,7 @@
 use super::{
     object::Object,
-    plane::{MarkStatus, Plane},
-    util::Direction,
+    plane::MarkStatus,
+    util::{Direction, Point},
 };
 
 #[derive(Debug)]
@@ -27,11 +27,105 @@ impl Command {
     pub fn tick(&self) -> &u32 {
         &self.tick
     }
+
+    pub fn command_type(&self) -> &CommandType {
+        &self.command_type
+    }
+
+    pub fn condition(&self) -> Option<&CommandCondition> {
+        self.command_condition.as_ref()
+    }
+
+    pub(crate) fn is_due(&self, position: &Point, objects: &[Object], tick: u32) -> bool {
+        let arrived_at = |is_type: fn(&Object) -> bool, label: &u8| {
+            objects
+                .iter()
+                .any(|o| is_type(o) && o.label() == Some(label) && o.position() == position)
+        };
+
+        match &self.command_condition {
+            None => true,
+            Some(CommandCondition::Delay(delay)) => tick >= self.tick + *delay as u32,
+            Some(CommandCondition::ArriveAirport(label)) => arrived_at(Object::is_airport, label),
+            Some(CommandCondition::ArriveBeacon(label)) => arrived_at(Object::is_beacon, label),
+        }
+    }
+
+    // Reads a command in the syntax the terminal game accepts, naming the
+    // character that doesn't fit so scripted input can say what went wrong.
+    // Returns the plane label as typed
+    pub fn parse(
+        input: &str,
+        objects: &[Object],
+        tick: u32,
+    ) -> Result<(char, Self), CommandBuildError> {
+        let mut chars = input.chars();
+        let mut next = || chars.next().ok_or(CommandBuildError::Incomplete);
+
+        let plane = next().map_err(|_| CommandBuildError::Empty)?;
+        if !plane.is_alphabetic() {
+            return Err(CommandBuildError::UnknownPlane(plane));
+        }
+
+        let command_type = match next()? {
+            't' => match next()? {
+                'l' => CommandType::TurnLeft(45),
+                'L' => CommandType::TurnLeft(90),
+                'r' => CommandType::TurnRight(45),
+                'R' => CommandType::TurnRight(90),
+                dir => CommandType::Turn(
+                    Direction::try_from(dir).map_err(|_| CommandBuildError::BadDirection(dir))?,
+                ),
+            },
+            'h' => match next()? {
+                'r' => CommandType::Circle { clockwise: true },
+                'l' => CommandType::Circle { clockwise: false },
+                ch => return Err(CommandBuildError::BadDirection(ch)),
+            },
+            'g' => {
+                let kind = next()?;
+                let label = next()?;
+                CommandType::DirectTo(to_waypoint(kind, label, objects)?)
+            }
+            'c' => CommandType::Climb(to_altitude(next()?)?),
+            'd' => CommandType::Dive(to_altitude(next()?)?),
+            'm' => CommandType::ChangeMark(MarkStatus::Marked),
+            'u' => CommandType::ChangeMark(MarkStatus::Unmarked),
+            'i' => CommandType::ChangeMark(MarkStatus::Ignored),
+            ch => return Err(CommandBuildError::UnknownCommand(ch)),
+        };
+
+        // The writer pads mark commands so every command type is two
+        // characters long
+        let condition_chars: Vec<char> = match command_type {
+            CommandType::ChangeMark(_) => chars.as_str().trim_start_matches('_').chars().collect(),
+            _ => chars.collect(),
+        };
+        let command_condition = match condition_chars[..] {
+            [] => None,
+            ['a', kind @ ('a' | 'b'), label] => match to_waypoint(kind, label, objects)? {
+                Waypoint::Airport(airport) => Some(CommandCondition::ArriveAirport(airport)),
+                Waypoint::Beacon(beacon) => Some(CommandCondition::ArriveBeacon(beacon)),
+                Waypoint::Exit(_) => unreachable!(),
+            },
+            ['i', tens, ones] if tens.is_ascii_digit() && ones.is_ascii_digit() => Some(
+                CommandCondition::Delay(to_digit(tens).unwrap() * 10 + to_digit(ones).unwrap()),
+            ),
+            ['a'] | ['a', 'a' | 'b'] | ['i'] => return Err(CommandBuildError::Incomplete),
+            ['i', tens] if tens.is_ascii_digit() => return Err(CommandBuildError::Incomplete),
+            _ => return Err(CommandBuildError::Malformed(input.to_owned())),
+        };
+
+        Ok((plane, Command::new(command_type, command_condition, tick)))
+    }
 }
 
 impl std::fmt::Display for Command {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        todo!()
+        match &self.command_condition {
+            Some(condition) => write!(f, "{} {}", self.command_type, condition),
+            None => write!(f, "{}", self.command_type),
+        }
     }
 }
 
@@ -40,244 +134,221 @@ pub enum CommandType {
     Climb(u8),
     Dive(u8),
     Turn(Direction),
+    TurnLeft(u16),
+    TurnRight(u16),
+    Circle { clockwise: bool },
+    DirectTo(Waypoint),
     ChangeMark(MarkStatus),
 }
 
-#[derive(Debug)]
-pub enum CommandCondition {
-    ArriveAirport(u8),
-    ArriveBeacon(u8),
-    Delay(u8),
-}
-
-#[derive(Debug)]
-pub struct CommandWriter {
-    cur_string: String,
-}
-
-impl CommandWriter {
-    pub fn new() -> Self {
-        Self {
-            cur_string: String::new(),
+impl std::fmt::Display for CommandType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommandType::Climb(altitude) => write!(f, "climb {altitude}000 feet"),
+            CommandType::Dive(altitude) => write!(f, "dive {altitude}000 feet"),
+            CommandType::Turn(direction) => write!(f, "turn {direction}"),
+            CommandType::TurnLeft(degrees) => write!(f, "turn left {degrees} degrees"),
+            CommandType::TurnRight(degrees) => write!(f, "turn right {degrees} degrees"),
+            CommandType::Circle { clockwise: true } => write!(f, "circle clockwise"),
+            CommandType::Circle { clockwise: false } => write!(f, "circle counterclockwise"),
+            CommandType::DirectTo(waypoint) => write!(f, "fly to {waypoint}"),
+            CommandType::ChangeMark(MarkStatus::Marked) => write!(f, "mark"),
+            CommandType::ChangeMark(MarkStatus::Unmarked) => write!(f, "unmark"),
+            CommandType::ChangeMark(MarkStatus::Ignored) => write!(f, "ignore"),
         }
     }
+}
 
-    pub fn push(&mut self, input: char) {
-        if self.cur_string.is_empty() && input.is_alphabetic() {
-            self.cur_string.push(input);
-            return;
-        }
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Waypoint {
+    Airport(u8),
+    Beacon(u8),
+    Exit(u8),
+}
 
-        let prev_char = self.cur_string.chars().last().unwrap();
-        match self.cur_string.len() {
-            1 => match input {
-                't' | 'c' | 'd' => self.cur_string.push(input),
-                'm' | 'u' | 'i' => {
-                    self.cur_string.push(input);
-                    self.cur_string.push('_');
-                }
-                _ => (),
-            },
-            2 => {
-                if matches!(
-                    (prev_char, input),
-                    ('t', 'q' | 'w' | 'e' | 'a' | 'd' | 'z' | 'x' | 'c') | ('c' | 'd', '0'..='9')
-                ) {
-                    self.cur_string.push(input)
-                }
-            }
-            3 => {
-                if matches!(input, 'a' | 'i') {
-                    self.cur_string.push(input)
-                }
-            }
-            4 => {
-                if matches!((prev_char, input), ('i', '0'..='9') | ('a', 'a' | 'b')) {
-                    self.cur_string.push(input)
-                }
-            }
-            5 => {
-                if matches!((prev_char, input), ('a' | 'b' | '0'..='9', '0'..='9')) {
-                    self.cur_string.push(input)
-                }
-            }
-            _ => (),
+impl Waypoint {
+    pub fn is_object(&self, object: &Object) -> bool {
+        match (self, object) {
+            (Waypoint::Airport(label), Object::Airport { .. })
+            | (Waypoint::Beacon(label), Object::Beacon { .. })
+            | (Waypoint::Exit(label), Object::Exit { .. }) => object.label() == Some(label),
+            _ => false,
         }
     }
+}
 
-    pub fn pop(&mut self) {
-        if let Some('_') = self.cur_string.pop() {
-            self.cur_string.pop();
+impl std::fmt::Display for Waypoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Waypoint::Airport(label) => write!(f, "airport {label}"),
+            Waypoint::Beacon(label) => write!(f, "beacon {label}"),
+            Waypoint::Exit(label) => write!(f, "exit {label}"),
         }
     }
+}
 
-    pub fn clear(&mut self) {
-        self.cur_string.clear();
-    }
+#[derive(Debug)]
+pub enum CommandCondition {
+    ArriveAirport(u8),
+    ArriveBeacon(u8),
+    Delay(u8),
+}
 
-    //? Change Option<Command> to Result<Command, CommandBuildError>
-    pub fn build<'a>(
-        self,
-        planes: &'a [Plane],
-        objects: &'a [Object],
-        tick: u32,
-    ) -> Option<(Command, char)> {
-        if self.cur_string.is_empty() {
-            return None;
+impl std::fmt::Display for CommandCondition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommandCondition::ArriveAirport(label) => write!(f, "at airport {label}"),
+            CommandCondition::ArriveBeacon(label) => write!(f, "at beacon {label}"),
+            CommandCondition::Delay(delay) => write!(f, "in {delay} seconds"),
         }
+    }
+}
 
-        let mut string_iter = self.cur_string.chars();
-
-        let plane = string_iter.next().unwrap();
-        if !planes.iter().any(|p| p.label() == &plane) {
-            return None;
-        }
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommandBuildError {
+    Empty,
+    Incomplete,
+    Malformed(String),
+    UnknownPlane(char),
+    UnknownCommand(char),
+    BadDirection(char),
+    BadAltitude(char),
+    BadWaypoint(char),
+    NoSuchBeacon(u8),
+    NoSuchAirport(u8),
+    NoSuchExit(u8),
+}
 
-        let command_type_chars: [char; 2] = string_iter
-            .by_ref()
-            .take(2)
-            .collect::<Vec<char>>()
-            .try_into()
-            .ok()?;
-        let command_type = match command_type_chars {
-            ['t', dir] => Some(CommandType::Turn(Direction::try_from(dir).ok()?)),
-            ['c', alt] => Some(CommandType::Climb(to_digit(alt)?)),
-            ['d', alt] => Some(CommandType::Dive(to_digit(alt)?)),
-            ['m', _] => Some(CommandType::ChangeMark(MarkStatus::Marked)),
-            ['u', _] => Some(CommandType::ChangeMark(MarkStatus::Unmarked)),
-            ['i', _] => Some(CommandType::ChangeMark(MarkStatus::Ignored)),
-            _ => None,
-        }?;
-
-        let condition_chars_vec: Vec<char> = string_iter.collect();
-        if condition_chars_vec.is_empty() {
-            return Some((Command::new(command_type, None, tick), plane));
+impl std::fmt::Display for CommandBuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommandBuildError::Empty => write!(f, "no command entered"),
+            CommandBuildError::Incomplete => write!(f, "command is incomplete"),
+            CommandBuildError::Malformed(input) => write!(f, "malformed command '{input}'"),
+            CommandBuildError::UnknownPlane(plane) => write!(f, "no plane labeled '{plane}'"),
+            CommandBuildError::UnknownCommand(ch) => write!(f, "unknown command '{ch}'"),
+            CommandBuildError::BadDirection(ch) => write!(f, "'{ch}' is not a direction"),
+            CommandBuildError::BadAltitude(ch) => write!(f, "'{ch}' is not an altitude"),
+            CommandBuildError::BadWaypoint(ch) => {
+                write!(f, "'{ch}' is not an airport, beacon or exit")
+            }
+            CommandBuildError::NoSuchBeacon(beacon) => write!(f, "no beacon {beacon}"),
+            CommandBuildError::NoSuchAirport(airport) => write!(f, "no airport {airport}"),
+            CommandBuildError::NoSuchExit(exit) => write!(f, "no exit {exit}"),
         }
+    }
+}
 
-        let condition_chars: [char; 3] = condition_chars_vec.try_into().ok()?;
-        let command_condition = match condition_chars {
-            ['a', 'a', label] => {
-                let airport = to_digit(label)?;
-                if !objects
-                    .iter()
-                    .any(|o| o.is_airport() && o.label().unwrap() == &airport)
-                {
-                    return None;
-                }
-
-                Some(CommandCondition::ArriveAirport(airport))
-            }
-            ['a', 'b', label] => {
-                let beacon = to_digit(label)?;
-                if !objects
-                    .iter()
-                    .any(|o| o.is_beacon() && o.label().unwrap() == &beacon)
-                {
-                    return None;
-                }
-
-                Some(CommandCondition::ArriveBeacon(beacon))
-            }
-            ['i', digit_tens, digit_ones] => Some(CommandCondition::Delay(
-                to_digit(digit_tens)? * 10u8 + to_digit(digit_ones)?,
-            )),
-            _ => None,
-        };
+impl std::error::Error for CommandBuildError {}
 
-        Some((Command::new(command_type, command_condition, tick), plane))
-    }
+fn to_digit(ch: char) -> Option<u8> {
+    Some(ch.to_digit(10)? as u8)
 }
 
-impl Default for CommandWriter {
-    fn default() -> Self {
-        Self::new()
-    }
+// Altitudes are single digits in thousands of feet, so any digit is in range
+fn to_altitude(ch: char) -> Result<u8, CommandBuildError> {
+    to_digit(ch).ok_or(CommandBuildError::BadAltitude(ch))
 }
 
-impl std::fmt::Display for CommandWriter {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut display_string = String::new();
-        let mut chars = self.cur_string.chars();
+fn to_waypoint(kind: char, label: char, objects: &[Object]) -> Result<Waypoint, CommandBuildError> {
+    let label = to_digit(label).ok_or(CommandBuildError::Incomplete)?;
+    let (waypoint, missing) = match kind {
+        'a' => (
+            Waypoint::Airport(label),
+            CommandBuildError::NoSuchAirport(label),
+        ),
+        'b' => (
+            Waypoint::Beacon(label),
+            CommandBuildError::NoSuchBeacon(label),
+        ),
+        'e' => (Waypoint::Exit(label), CommandBuildError::NoSuchExit(label)),
+        _ => return Err(CommandBuildError::BadWaypoint(kind)),
+    };
+
+    if !objects.iter().any(|o| waypoint.is_object(o)) {
+        return Err(missing);
+    }
 
-        let plane = match chars.next() {
-            Some(p) => format!("{p}:"),
-            None => return Ok(()),
-        };
-        display_string.push_str(&plane);
-
-        let command_type = match chars.next() {
-            Some('c') => " climb",
-            Some('d') => " dive",
-            Some('t') => " turn",
-            Some('m') => " mark",
-            Some('u') => " unmark",
-            Some('i') => " ignore",
-            Some(x) => panic!("Invalid command character encountered: {x}"),
-            None => return write!(f, "{display_string}"),
-        };
-        display_string.push_str(command_type);
-
-        let command_arg = match chars.next() {
-            Some('_') => "".to_owned(),
-            Some(ch) if is_direction(ch) => format!(" {}", Direction::try_from(ch).unwrap()),
-            Some(num) if num.is_numeric() => format!(" {num}000 feet"),
-            Some(x) => panic!("Invalid command argument encountered: {x}"),
-            None => return write!(f, "{display_string}"),
-        };
-        display_string.push_str(&command_arg);
+    Ok(waypoint)
+}
 
-        let condition_type = match chars.next() {
-            Some('i') => " in",
-            Some('a') => " at",
-            Some(x) => panic!("Invalid condition type encountered: {x}"),
-            None => return write!(f, "{display_string}"),
-        };
-        display_string.push_str(condition_type);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_errors() {
+        let objects = [Object::Beacon {
+            position: Point(2, 2),
+            label: Some(2),
+        }];
+        let cases = [
+            ("", CommandBuildError::Empty),
+            ("a", CommandBuildError::Incomplete),
+            ("at", CommandBuildError::Incomplete),
+            ("ac3i1", CommandBuildError::Incomplete),
+            ("1c3", CommandBuildError::UnknownPlane('1')),
+            ("ab3", CommandBuildError::UnknownCommand('b')),
+            ("atp", CommandBuildError::BadDirection('p')),
+            ("ahx", CommandBuildError::BadDirection('x')),
+            ("acx", CommandBuildError::BadAltitude('x')),
+            ("agx1", CommandBuildError::BadWaypoint('x')),
+            ("agb7", CommandBuildError::NoSuchBeacon(7)),
+            ("aga7", CommandBuildError::NoSuchAirport(7)),
+            ("age7", CommandBuildError::NoSuchExit(7)),
+            ("ac3ab7", CommandBuildError::NoSuchBeacon(7)),
+            ("ac3xyz", CommandBuildError::Malformed("ac3xyz".to_owned())),
+            ("ac3i1x", CommandBuildError::Malformed("ac3i1x".to_owned())),
+            (
+                "ac3aa1x",
+                CommandBuildError::Malformed("ac3aa1x".to_owned()),
+            ),
+        ];
+
+        for (input, error) in cases {
+            assert_eq!(
+                Command::parse(input, &objects, 0).err(),
+                Some(error),
+                "input: {input}"
+            );
+        }
+    }
 
-        let is_delay: bool;
-        let condition_arg_1 = match chars.next() {
-            Some('a') => {
-                is_delay = false;
-                " airport:".to_owned()
-            }
-            Some('b') => {
-                is_delay = false;
-                " beacon:".to_owned()
-            }
-            Some(num) if num.is_numeric() => {
-                is_delay = true;
-                format!(" {num}")
-            }
-            Some(x) => panic!("Invalid condition argument encountered: {x}"),
-            None => return write!(f, "{display_string}"),
-        };
-        display_string.push_str(&condition_arg_1);
-
-        let condition_arg_2 = match chars.next() {
-            Some(num) if num.is_numeric() => {
-                if is_delay {
-                    format!("{num} seconds")
-                } else {
-                    format!(" {num}")
-                }
-            }
-            Some(x) => panic!("Invalid condition argument encountered: {x}"),
-            None => return write!(f, "{display_string}"),
-        };
-        display_string.push_str(&condition_arg_2);
+    #[test]
+    fn delay_fires_at_its_tick() {
+        let (_, command) = Command::parse("ac3i05", &[], 10).unwrap();
 
-        write!(f, "{display_string}")
+        assert!(!command.is_due(&Point(5, 5), &[], 14));
+        assert!(command.is_due(&Point(5, 5), &[], 15));
+        assert!(command.is_due(&Point(5, 5), &[], 16));
     }
-}
 
-fn to_digit(ch: char) -> Option<u8> {
-    Some(ch.to_digit(10)? as u8)
-}
+    #[test]
+    fn arrival_fires_over_the_object() {
+        let objects = [
+            Object::Airport {
+                position: Point(3, 3),
+                direction: Direction::East,
+                label: Some(1),
+            },
+            Object::Beacon {
+                position: Point(6, 6),
+                label: Some(1),
+            },
+            Object::Beacon {
+                position: Point(8, 2),
+                label: Some(2),
+            },
+        ];
+
+        let (_, command) = Command::parse("ac3aa1", &objects, 0).unwrap();
+        assert!(command.is_due(&Point(3, 3), &objects, 0));
+        assert!(!command.is_due(&Point(6, 6), &objects, 0));
+        assert!(!command.is_due(&Point(3, 4), &objects, 0));
 
-fn is_direction(ch: char) -> bool {
-    use super::util::DIRECTION_CHARS;
-    if !ch.is_alphabetic() {
-        return false;
+        let (_, command) = Command::parse("ac3ab1", &objects, 0).unwrap();
+        assert!(command.is_due(&Point(6, 6), &objects, 0));
+        assert!(!command.is_due(&Point(3, 3), &objects, 0));
+        assert!(!command.is_due(&Point(8, 2), &objects, 0));
     }
-    DIRECTION_CHARS.iter().any(|c| c == &ch)
 }
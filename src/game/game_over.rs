@@ -0,0 +1,35 @@
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameOver {
+    Collision(char, char),
+    IllegalExit(char),
+    WrongExitAltitude(char, u8),
+    WrongAirport(char, u8),
+    WrongLandingDirection(char, u8),
+    GroundCrash(char),
+    OutOfFuel(char),
+}
+
+impl std::fmt::Display for GameOver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameOver::Collision(plane_a, plane_b) => {
+                write!(f, "Plane '{plane_a}' collided with plane '{plane_b}'")
+            }
+            GameOver::IllegalExit(plane) => write!(f, "Plane '{plane}' illegally left the map"),
+            GameOver::WrongExitAltitude(plane, altitude) => write!(
+                f,
+                "Plane '{plane}' left the map at {altitude}000 feet instead of {}000 feet",
+                super::EXIT_ALTITUDE
+            ),
+            GameOver::WrongAirport(plane, airport) => {
+                write!(f, "Plane '{plane}' landed at the wrong airport ({airport})")
+            }
+            GameOver::WrongLandingDirection(plane, airport) => write!(
+                f,
+                "Plane '{plane}' landed at airport {airport} from the wrong direction"
+            ),
+            GameOver::GroundCrash(plane) => write!(f, "Plane '{plane}' crashed into the ground"),
+            GameOver::OutOfFuel(plane) => write!(f, "Plane '{plane}' ran out of fuel"),
+        }
+    }
+}
@@ -1,3 +1,15 @@
+use std::{
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{RootSchema, Schema},
+    schema_for, JsonSchema,
+};
 use serde::Deserialize;
 
 use super::{
@@ -8,17 +20,79 @@ use super::{
 const MINIMUM_WIDTH: u16 = 5;
 const MINIMUM_HEIGHT: u16 = 5;
 
-#[derive(Debug, Deserialize)]
+fn default_jet_chance() -> f32 {
+    0.5
+}
+
+// schemars' range attribute has no exclusive bounds, and a tick rate of 0
+// would never advance the game
+fn tick_rate_schema(gen: &mut SchemaGenerator) -> Schema {
+    let mut schema = f32::json_schema(gen).into_object();
+    schema.number().exclusive_minimum = Some(0.0);
+
+    Schema::Object(schema)
+}
+
+/// Maps for zen_atc
+#[derive(Debug, Deserialize, JsonSchema)]
+#[schemars(title = "Zen ATC Map Schema")]
 pub struct Map {
+    /// Width of the map
+    #[schemars(range(min = 5))]
     width: u16,
+    /// Height of the map
+    #[schemars(range(min = 5))]
     height: u16,
+    /// Chance of a plane spawning each tick
+    #[schemars(range(min = 0.0, max = 1.0))]
     spawn_chance: f32,
+    /// Seconds per tick
+    #[schemars(schema_with = "tick_rate_schema")]
     tick_rate: f32,
+    /// Chance of a spawned plane being a jet rather than a prop
+    #[serde(default = "default_jet_chance")]
+    #[schemars(range(min = 0.0, max = 1.0))]
+    jet_chance: f32,
     objects: Vec<Object>,
     lines: Vec<Line>,
 }
 
 impl Map {
+    pub fn load(path: &Path) -> Result<Self, MapLoadError> {
+        let to_error = |kind| MapLoadError::new(path, kind);
+
+        let file = fs::File::open(path).map_err(|e| to_error(MapLoadErrorKind::Io(e)))?;
+        Map::from_reader(file).map_err(to_error)
+    }
+
+    // Unlike `load`, leaves validation to the caller
+    pub fn read(path: &Path) -> Result<Self, MapLoadError> {
+        let to_error = |kind| MapLoadError::new(path, kind);
+
+        let contents = fs::read_to_string(path).map_err(|e| to_error(MapLoadErrorKind::Io(e)))?;
+        Map::parse(&contents).map_err(|e| to_error(MapLoadErrorKind::Parse(e)))
+    }
+
+    pub fn from_reader(mut reader: impl Read) -> Result<Self, MapLoadErrorKind> {
+        let mut contents = String::new();
+        reader
+            .read_to_string(&mut contents)
+            .map_err(MapLoadErrorKind::Io)?;
+
+        contents.parse()
+    }
+
+    fn parse(contents: &str) -> Result<Self, serde_json::Error> {
+        let mut map: Map = serde_json::from_str(contents)?;
+        map.label();
+
+        Ok(map)
+    }
+
+    pub fn schema() -> RootSchema {
+        schema_for!(Map)
+    }
+
     fn label(&mut self) {
         let mut count_airports = 0;
         let mut count_beacons = 0;
@@ -47,11 +121,11 @@ impl Map {
 
         for line in self.lines.iter() {
             if !line.is_valid_slope() {
-                return Err(MapError::Line(LineError::InvalidSlope(line)));
+                return Err(MapError::Line(LineError::InvalidSlope(line.clone())));
             }
 
             if !line.is_within_bounds(*width, *height) {
-                return Err(MapError::Line(LineError::OutOfBounds(line)));
+                return Err(MapError::Line(LineError::OutOfBounds(line.clone())));
             }
         }
 
@@ -63,7 +137,7 @@ impl Map {
             for object_b in self.objects.iter().skip(k + 1) {
                 if object_a.position() == object_b.position() {
                     return Err(MapError::ObjectPlacement(
-                        ObjectPlacementError::ConflictedSpace(object_a, object_b),
+                        ObjectPlacementError::ConflictedSpace(object_a.clone(), object_b.clone()),
                     ));
                 }
             }
@@ -84,7 +158,7 @@ impl Map {
 
             if *x == 0 || *x > width || *y == 0 || *y > height {
                 return Err(MapError::ObjectPlacement(
-                    ObjectPlacementError::OutOfBounds(object),
+                    ObjectPlacementError::OutOfBounds(object.clone()),
                 ));
             }
         }
@@ -92,14 +166,20 @@ impl Map {
         for exit in exits {
             let Point(x, y) = exit.position();
 
+            if *x > width + 1 || *y > height + 1 {
+                return Err(MapError::ObjectPlacement(
+                    ObjectPlacementError::OutOfBounds(exit.clone()),
+                ));
+            }
+
             let border_left = *x == 0;
             let border_top = *y == 0;
             let border_right = *x == width + 1;
-            let border_bottom = *x == height + 1;
+            let border_bottom = *y == height + 1;
 
             if !(border_left || border_top || border_right || border_bottom) {
                 return Err(MapError::ObjectPlacement(ObjectPlacementError::NotOnEdge(
-                    exit,
+                    exit.clone(),
                 )));
             }
 
@@ -116,7 +196,7 @@ impl Map {
 
             if !on_valid_edge {
                 return Err(MapError::ObjectPlacement(
-                    ObjectPlacementError::InvalidDirection(exit),
+                    ObjectPlacementError::InvalidDirection(exit.clone()),
                 ));
             }
         }
@@ -124,7 +204,7 @@ impl Map {
         Ok(())
     }
 
-    fn validate(&self) -> Result<(), MapError> {
+    pub fn validate(&self) -> Result<(), MapError> {
         if self.width < MINIMUM_WIDTH || self.height < MINIMUM_HEIGHT {
             return Err(MapError::InvalidSize(self.width, self.height));
         }
@@ -133,6 +213,14 @@ impl Map {
             return Err(MapError::InvalidSpawnChance(self.spawn_chance));
         }
 
+        if self.jet_chance < 0.0 || self.jet_chance > 1.0 {
+            return Err(MapError::InvalidJetChance(self.jet_chance));
+        }
+
+        if self.tick_rate <= 0.0 || !self.tick_rate.is_finite() {
+            return Err(MapError::InvalidTickRate(self.tick_rate));
+        }
+
         self.validate_lines()?;
         self.validate_collisions()?;
         self.validate_objects()?;
@@ -152,15 +240,73 @@ impl Map {
         &self.tick_rate
     }
 
+    pub fn spawn_chance(&self) -> &f32 {
+        &self.spawn_chance
+    }
+
+    pub fn jet_chance(&self) -> &f32 {
+        &self.jet_chance
+    }
+
     pub fn objects(&self) -> &[Object] {
         &self.objects
     }
+
+    pub fn lines(&self) -> &[Line] {
+        &self.lines
+    }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Clone)]
+#[serde(from = "LineRepr")]
 pub struct Line(Point, Point);
 
+// Lines are written as `{ "start": .., "end": .. }` in the schema, but older
+// maps store them as a bare pair of points
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum LineRepr {
+    Object { start: Point, end: Point },
+    Array(Point, Point),
+}
+
+impl JsonSchema for Line {
+    fn schema_name() -> String {
+        "Line".to_owned()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        LineRepr::json_schema(gen)
+    }
+}
+
+impl From<LineRepr> for Line {
+    fn from(repr: LineRepr) -> Self {
+        match repr {
+            LineRepr::Object { start, end } => Line(start, end),
+            LineRepr::Array(start, end) => Line(start, end),
+        }
+    }
+}
+
 impl Line {
+    pub fn points(&self) -> Vec<Point> {
+        let Point(x1, y1) = self.0;
+        let Point(x2, y2) = self.1;
+        let steps = x1.abs_diff(x2).max(y1.abs_diff(y2));
+        let dx = (x2 as i16 - x1 as i16).signum();
+        let dy = (y2 as i16 - y1 as i16).signum();
+
+        (0..=steps as i16)
+            .map(|k| {
+                Point(
+                    x1.saturating_add_signed(k * dx),
+                    y1.saturating_add_signed(k * dy),
+                )
+            })
+            .collect()
+    }
+
     fn is_valid_slope(&self) -> bool {
         let dx = self.0 .0.abs_diff(self.1 .0);
         let dy = self.0 .1.abs_diff(self.1 .1);
@@ -188,15 +334,88 @@ impl Line {
     }
 }
 
+impl FromStr for Map {
+    type Err = MapLoadErrorKind;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let map = Map::parse(s).map_err(MapLoadErrorKind::Parse)?;
+        map.validate().map_err(MapLoadErrorKind::Invalid)?;
+
+        Ok(map)
+    }
+}
+
 #[derive(Debug)]
-enum MapError<'a> {
+pub struct MapLoadError {
+    path: PathBuf,
+    kind: MapLoadErrorKind,
+}
+
+impl MapLoadError {
+    pub(crate) fn new(path: &Path, kind: MapLoadErrorKind) -> Self {
+        Self {
+            path: path.to_owned(),
+            kind,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum MapLoadErrorKind {
+    Io(io::Error),
+    Parse(serde_json::Error),
+    Invalid(MapError),
+}
+
+impl std::fmt::Display for MapLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let path = self.path.display();
+
+        match &self.kind {
+            MapLoadErrorKind::Io(e) => write!(f, "could not read {path}: {e}"),
+            MapLoadErrorKind::Parse(e) => write!(f, "could not parse {path}: {e}"),
+            MapLoadErrorKind::Invalid(e) => write!(f, "invalid map {path}: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for MapLoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.kind.source()
+    }
+}
+
+impl std::fmt::Display for MapLoadErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MapLoadErrorKind::Io(e) => write!(f, "could not read map: {e}"),
+            MapLoadErrorKind::Parse(e) => write!(f, "could not parse map: {e}"),
+            MapLoadErrorKind::Invalid(e) => write!(f, "invalid map: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for MapLoadErrorKind {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MapLoadErrorKind::Io(e) => Some(e),
+            MapLoadErrorKind::Parse(e) => Some(e),
+            MapLoadErrorKind::Invalid(e) => Some(e),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum MapError {
     InvalidSize(u16, u16),
     InvalidSpawnChance(f32),
-    ObjectPlacement(ObjectPlacementError<'a>),
-    Line(LineError<'a>),
+    InvalidJetChance(f32),
+    InvalidTickRate(f32),
+    ObjectPlacement(ObjectPlacementError),
+    Line(LineError),
 }
 
-impl std::fmt::Display for MapError<'_> {
+impl std::fmt::Display for MapError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             MapError::InvalidSize(width, height) => {
@@ -205,23 +424,29 @@ impl std::fmt::Display for MapError<'_> {
             MapError::InvalidSpawnChance(spawn_chance) => {
                 write!(f, "invalid spawn chance: {}", spawn_chance,)
             }
+            MapError::InvalidJetChance(jet_chance) => {
+                write!(f, "invalid jet chance: {}", jet_chance,)
+            }
+            MapError::InvalidTickRate(tick_rate) => {
+                write!(f, "invalid tick rate: {}", tick_rate,)
+            }
             MapError::ObjectPlacement(e) => write!(f, "object placement error: {e}",),
             MapError::Line(e) => write!(f, "line error: {e}",),
         }
     }
 }
 
-impl std::error::Error for MapError<'_> {}
+impl std::error::Error for MapError {}
 
 #[derive(Debug)]
-enum ObjectPlacementError<'a> {
-    OutOfBounds(&'a Object),
-    NotOnEdge(&'a Object),
-    InvalidDirection(&'a Object),
-    ConflictedSpace(&'a Object, &'a Object),
+pub enum ObjectPlacementError {
+    OutOfBounds(Object),
+    NotOnEdge(Object),
+    InvalidDirection(Object),
+    ConflictedSpace(Object, Object),
 }
 
-impl std::fmt::Display for ObjectPlacementError<'_> {
+impl std::fmt::Display for ObjectPlacementError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ObjectPlacementError::ConflictedSpace(object_a, object_b) => write!(
@@ -250,15 +475,15 @@ impl std::fmt::Display for ObjectPlacementError<'_> {
     }
 }
 
-impl std::error::Error for ObjectPlacementError<'_> {}
+impl std::error::Error for ObjectPlacementError {}
 
 #[derive(Debug)]
-enum LineError<'a> {
-    OutOfBounds(&'a Line),
-    InvalidSlope(&'a Line),
+pub enum LineError {
+    OutOfBounds(Line),
+    InvalidSlope(Line),
 }
 
-impl std::fmt::Display for LineError<'_> {
+impl std::fmt::Display for LineError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             LineError::InvalidSlope(line) => write!(
@@ -275,4 +500,236 @@ impl std::fmt::Display for LineError<'_> {
     }
 }
 
-impl std::error::Error for LineError<'_> {}
+impl std::error::Error for LineError {}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    fn validate(fixture: Value) -> Result<(), MapError> {
+        let mut map: Map = serde_json::from_value(fixture).unwrap();
+        map.label();
+        map.validate()
+    }
+
+    fn fixture(objects: Value, lines: Value) -> Value {
+        json!({
+            "width": 10,
+            "height": 8,
+            "tick_rate": 1.0,
+            "spawn_chance": 0.1,
+            "objects": objects,
+            "lines": lines,
+        })
+    }
+
+    #[test]
+    fn valid_map() {
+        let map = fixture(
+            json!([
+                { "Airport": { "position": [3, 3], "direction": "East" } },
+                { "Beacon": { "position": [5, 5] } },
+                { "Exit": { "position": [0, 4], "direction": "West" } },
+                { "Exit": { "position": [11, 4], "direction": "East" } },
+                { "Exit": { "position": [5, 0], "direction": "North" } },
+                { "Exit": { "position": [5, 9], "direction": "South" } },
+                { "Exit": { "position": [11, 9], "direction": "Southeast" } },
+            ]),
+            json!([[[1, 4], [10, 4]], [[5, 1], [5, 8]], [[1, 1], [8, 8]]]),
+        );
+
+        assert!(validate(map).is_ok());
+    }
+
+    #[test]
+    fn line_formats() {
+        let map = fixture(
+            json!([]),
+            json!([{ "start": [1, 4], "end": [10, 4] }, [[5, 1], [5, 8]]]),
+        );
+
+        assert!(validate(map).is_ok());
+    }
+
+    #[test]
+    fn load_schema_example() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("maps/default.json");
+        let map = Map::load(&path).unwrap();
+
+        assert_eq!(map.lines().len(), 6);
+        assert_eq!(map.lines()[0].points().len(), 30);
+    }
+
+    #[test]
+    fn checked_in_schema_is_current() {
+        let generated = serde_json::to_string_pretty(&Map::schema()).unwrap();
+        let checked_in = include_str!("../../zen_atc_map_schema.json");
+
+        assert_eq!(
+            checked_in.trim_end(),
+            generated,
+            "zen_atc_map_schema.json is out of date, regenerate it with `zen_atc schema`"
+        );
+    }
+
+    #[test]
+    fn schema_excludes_zero_tick_rate() {
+        let schema = serde_json::to_value(Map::schema()).unwrap();
+        assert_eq!(
+            schema["properties"]["tick_rate"]["exclusiveMinimum"],
+            json!(0.0)
+        );
+    }
+
+    #[test]
+    fn from_str_labels_and_validates() {
+        let map: Map = fixture(json!([{ "Beacon": { "position": [5, 5] } }]), json!([]))
+            .to_string()
+            .parse()
+            .unwrap();
+        assert_eq!(map.objects()[0].label(), Some(&0));
+
+        let mut invalid = fixture(json!([]), json!([]));
+        invalid["spawn_chance"] = json!(1.5);
+        assert!(matches!(
+            invalid.to_string().parse::<Map>(),
+            Err(MapLoadErrorKind::Invalid(MapError::InvalidSpawnChance(_)))
+        ));
+        assert!(matches!(
+            Map::from_reader("{".as_bytes()),
+            Err(MapLoadErrorKind::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn invalid_size() {
+        let mut map = fixture(json!([]), json!([]));
+        map["width"] = json!(MINIMUM_WIDTH - 1);
+
+        assert!(matches!(validate(map), Err(MapError::InvalidSize(4, 8))));
+    }
+
+    #[test]
+    fn invalid_spawn_chance() {
+        let mut map = fixture(json!([]), json!([]));
+        map["spawn_chance"] = json!(1.5);
+
+        assert!(matches!(
+            validate(map),
+            Err(MapError::InvalidSpawnChance(_))
+        ));
+    }
+
+    #[test]
+    fn invalid_jet_chance() {
+        let mut map = fixture(json!([]), json!([]));
+        map["jet_chance"] = json!(-0.5);
+
+        assert!(matches!(validate(map), Err(MapError::InvalidJetChance(_))));
+    }
+
+    #[test]
+    fn invalid_tick_rate() {
+        let mut map = fixture(json!([]), json!([]));
+        map["tick_rate"] = json!(0.0);
+
+        assert!(matches!(validate(map), Err(MapError::InvalidTickRate(_))));
+    }
+
+    #[test]
+    fn object_out_of_bounds() {
+        let map = fixture(json!([{ "Beacon": { "position": [0, 3] } }]), json!([]));
+        assert!(matches!(
+            validate(map),
+            Err(MapError::ObjectPlacement(
+                ObjectPlacementError::OutOfBounds(_)
+            ))
+        ));
+
+        let map = fixture(
+            json!([{ "Exit": { "position": [12, 3], "direction": "East" } }]),
+            json!([]),
+        );
+        assert!(matches!(
+            validate(map),
+            Err(MapError::ObjectPlacement(
+                ObjectPlacementError::OutOfBounds(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn exit_not_on_edge() {
+        let map = fixture(
+            json!([{ "Exit": { "position": [3, 3], "direction": "North" } }]),
+            json!([]),
+        );
+
+        assert!(matches!(
+            validate(map),
+            Err(MapError::ObjectPlacement(ObjectPlacementError::NotOnEdge(
+                _
+            )))
+        ));
+    }
+
+    #[test]
+    fn exit_invalid_direction() {
+        let map = fixture(
+            json!([{ "Exit": { "position": [5, 9], "direction": "North" } }]),
+            json!([]),
+        );
+
+        assert!(matches!(
+            validate(map),
+            Err(MapError::ObjectPlacement(
+                ObjectPlacementError::InvalidDirection(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn objects_conflicted_space() {
+        let map = fixture(
+            json!([
+                { "Airport": { "position": [3, 3], "direction": "East" } },
+                { "Beacon": { "position": [3, 3] } },
+            ]),
+            json!([]),
+        );
+
+        assert!(matches!(
+            validate(map),
+            Err(MapError::ObjectPlacement(
+                ObjectPlacementError::ConflictedSpace(_, _)
+            ))
+        ));
+    }
+
+    #[test]
+    fn line_out_of_bounds() {
+        let map = fixture(json!([]), json!([[[0, 4], [10, 4]]]));
+
+        assert!(matches!(
+            validate(map),
+            Err(MapError::Line(LineError::OutOfBounds(_)))
+        ));
+    }
+
+    #[test]
+    fn line_invalid_slope() {
+        let map = fixture(json!([]), json!([[[1, 1], [3, 2]]]));
+        assert!(matches!(
+            validate(map),
+            Err(MapError::Line(LineError::InvalidSlope(_)))
+        ));
+
+        let map = fixture(json!([]), json!([[[2, 2], [2, 2]]]));
+        assert!(matches!(
+            validate(map),
+            Err(MapError::Line(LineError::InvalidSlope(_)))
+        ));
+    }
+}
@@ -1,102 +1,332 @@
-mod command;
-mod map;
-mod object;
-mod plane;
-mod util;
+pub(crate) mod command;
+pub(crate) mod game_over;
+pub(crate) mod map;
+pub(crate) mod object;
+pub(crate) mod plane;
+pub(crate) mod replay;
+pub(crate) mod util;
 
 use std::{fs, path::PathBuf, time::Duration};
 
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 
 use self::{
-    command::{Command, CommandWriter},
-    map::Map,
-    plane::Plane,
+    command::{Command, CommandBuildError},
+    game_over::GameOver,
+    map::{Map, MapLoadError, MapLoadErrorKind},
+    object::Object,
+    plane::{AircraftClass, Destination, Plane},
+    replay::{Replay, ReplayError},
+    util::{Direction, Point},
 };
 
+const PLANE_LABELS: std::ops::RangeInclusive<char> = 'a'..='z';
+const ENTRY_ALTITUDE: u8 = 7;
+const DEPARTURE_ALTITUDE: u8 = 5;
+const EXIT_ALTITUDE: u8 = 9;
+
 #[derive(Debug)]
 pub struct App {
     map: Map,
     planes: Vec<Plane>,
-    cur_command: CommandWriter,
     tick: u32,
+    seed: u64,
+    rng: ChaCha8Rng,
+    recorder: Replay,
+    planes_handled: u32,
+    game_over: Option<GameOver>,
 }
 
 impl App {
-    pub fn new(path: PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
-        let map: Map = serde_json::from_str(fs::read_to_string(path)?.as_str())?;
+    pub fn new(path: PathBuf, seed: Option<u64>) -> Result<Self, MapLoadError> {
+        let contents =
+            fs::read(&path).map_err(|e| MapLoadError::new(&path, MapLoadErrorKind::Io(e)))?;
+        let map =
+            Map::from_reader(contents.as_slice()).map_err(|kind| MapLoadError::new(&path, kind))?;
+        let path = fs::canonicalize(&path).unwrap_or(path);
+
+        let mut app = App::from_map(map, seed);
+        app.recorder = Replay::new(path, replay::hash_map_file(&contents), app.seed);
+
+        Ok(app)
+    }
+
+    // With no file behind the map, the replay of this game has no map to
+    // point back to
+    pub fn from_map(map: Map, seed: Option<u64>) -> Self {
+        let seed = seed.unwrap_or_else(rand::random);
 
-        Ok(Self {
+        Self {
             map,
             planes: Vec::new(),
-            cur_command: CommandWriter::new(),
             tick: 0,
-        })
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            recorder: Replay::new(PathBuf::new(), 0, seed),
+            planes_handled: 0,
+            game_over: None,
+        }
+    }
+
+    pub fn from_replay(replay: &Replay, map: Option<PathBuf>) -> Result<Self, ReplayError> {
+        let path = map.unwrap_or(replay.map().to_owned());
+        let app = App::new(path.clone(), Some(*replay.seed())).map_err(ReplayError::Map)?;
+        if app.recorder.map_hash() != replay.map_hash() {
+            return Err(ReplayError::MapChanged(path));
+        }
+
+        Ok(app)
+    }
+
+    // Issues the commands recorded for the current tick, then steps the game.
+    // A rejected command means this game has drifted from the recorded one
+    pub fn step_replay(&mut self, replay: &Replay) -> Result<(), ReplayError> {
+        let tick = self.tick;
+        let mut rejected = None;
+
+        for input in replay.commands_at(tick) {
+            if let Err(error) = self.issue_command(input) {
+                rejected.get_or_insert(ReplayError::Rejected {
+                    tick,
+                    input: input.to_owned(),
+                    error,
+                });
+            }
+        }
+        self.update();
+
+        match rejected {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.recorder
+    }
+
+    pub fn map(&self) -> &Map {
+        &self.map
+    }
+
+    pub fn planes(&self) -> &[Plane] {
+        &self.planes
     }
 
     pub fn dimensions(&self) -> (&u16, &u16) {
         (self.map.width(), self.map.height())
     }
 
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn tick(&self) -> u32 {
+        self.tick
+    }
+
+    pub fn planes_handled(&self) -> u32 {
+        self.planes_handled
+    }
+
     pub fn tick_duration(&self) -> Duration {
         Duration::from_secs_f32(*self.map.tick_rate())
     }
 
-    pub fn cur_command(&self) -> String {
-        self.cur_command.to_string()
+    pub fn is_over(&self) -> bool {
+        self.game_over.is_some()
+    }
+
+    pub fn game_over(&self) -> Option<&GameOver> {
+        self.game_over.as_ref()
     }
 
     pub fn update(&mut self) {
-        // todo!()
+        if self.is_over() {
+            return;
+        }
+
+        self.tick += 1;
+
+        for plane in self.planes.iter_mut() {
+            plane.update(self.map.objects(), self.tick);
+        }
+
+        self.resolve_planes();
+        if self.is_over() {
+            return;
+        }
+
+        self.spawn_planes();
+    }
+
+    fn resolve_planes(&mut self) {
+        let mut planes_handled = 0;
+        let mut game_over = None;
+
+        self.planes
+            .retain(|plane| match Self::resolve_plane(&self.map, plane) {
+                Ok(PlaneStatus::Flying) => true,
+                Ok(PlaneStatus::Arrived) => {
+                    planes_handled += 1;
+                    false
+                }
+                Err(e) => {
+                    game_over.get_or_insert(e);
+                    false
+                }
+            });
+
+        for (k, plane_a) in self.planes.iter().enumerate() {
+            for plane_b in self.planes.iter().skip(k + 1) {
+                if in_collision_range(
+                    plane_a.position(),
+                    *plane_a.altitude(),
+                    plane_b.position(),
+                    *plane_b.altitude(),
+                ) {
+                    game_over
+                        .get_or_insert(GameOver::Collision(*plane_a.label(), *plane_b.label()));
+                }
+            }
+        }
+
+        self.planes_handled += planes_handled;
+        self.game_over = game_over;
+    }
+
+    fn resolve_plane(map: &Map, plane: &Plane) -> Result<PlaneStatus, GameOver> {
+        let label = *plane.label();
+        let Point(x, y) = plane.position();
+        if *x == 0 || *y == 0 || x > map.width() || y > map.height() {
+            let exit = map
+                .objects()
+                .iter()
+                .find(|o| o.is_exit() && o.position() == plane.position());
+
+            return match exit {
+                Some(e) if plane.destination().is_object(e) => {
+                    if *plane.altitude() != EXIT_ALTITUDE {
+                        return Err(GameOver::WrongExitAltitude(label, *plane.altitude()));
+                    }
+
+                    Ok(PlaneStatus::Arrived)
+                }
+                _ => Err(GameOver::IllegalExit(label)),
+            };
+        }
+
+        // A plane still sitting on the runway with a climb ahead of it is
+        // taking off rather than landing
+        if *plane.altitude() != 0 || *plane.target_altitude() != 0 {
+            // Checked after arrival, so a plane that reaches its destination
+            // on its last move still counts
+            if plane.is_out_of_fuel() {
+                return Err(GameOver::OutOfFuel(label));
+            }
+
+            return Ok(PlaneStatus::Flying);
+        }
+
+        let airport = map
+            .objects()
+            .iter()
+            .find(|o| o.is_airport() && o.position() == plane.position());
+        let airport = match airport {
+            Some(a) => a,
+            None => return Err(GameOver::GroundCrash(label)),
+        };
+
+        let airport_label = *airport.label().unwrap();
+        if !plane.destination().is_object(airport) {
+            return Err(GameOver::WrongAirport(label, airport_label));
+        }
+
+        if Some(plane.direction()) != airport.direction() {
+            return Err(GameOver::WrongLandingDirection(label, airport_label));
+        }
+
+        Ok(PlaneStatus::Arrived)
     }
 
-    fn build_command(&mut self) {
-        let cur_command = std::mem::take(&mut self.cur_command);
-        let (command, plane) = match cur_command.build(&self.planes, self.map.objects(), self.tick)
-        {
-            Some(c) => c,
+    fn spawn_planes(&mut self) {
+        if self.rng.gen::<f32>() >= *self.map.spawn_chance() {
+            return;
+        }
+
+        let label = match PLANE_LABELS.clone().find(|l| {
+            !self
+                .planes
+                .iter()
+                .any(|p| p.label().eq_ignore_ascii_case(l))
+        }) {
+            Some(l) => l,
+            None => return,
+        };
+
+        let endpoints: Vec<&Object> = self
+            .map
+            .objects()
+            .iter()
+            .filter(|o| !o.is_beacon())
+            .collect();
+        // A plane can't appear on top of, or right next to, another
+        let origins: Vec<&&Object> = endpoints
+            .iter()
+            .filter(|o| {
+                let (position, _, altitude, _) = entry(o);
+                !self
+                    .planes
+                    .iter()
+                    .any(|p| in_collision_range(p.position(), *p.altitude(), &position, altitude))
+            })
+            .collect();
+        let origin = match origins.choose(&mut self.rng) {
+            Some(o) => **o,
+            None => return,
+        };
+
+        let destinations: Vec<&&Object> = endpoints
+            .iter()
+            .filter(|o| o.position() != origin.position())
+            .collect();
+        let destination = match destinations.choose(&mut self.rng) {
+            Some(d) => Destination::from_object(d).unwrap(),
             None => return,
         };
 
+        let (position, direction, altitude, target_altitude) = entry(origin);
+
+        let class = if self.rng.gen::<f32>() < *self.map.jet_chance() {
+            AircraftClass::Jet
+        } else {
+            AircraftClass::Prop
+        };
+
+        self.planes.push(Plane::new(
+            position,
+            direction,
+            label,
+            class,
+            destination,
+            altitude,
+            target_altitude,
+        ));
+    }
+
+    pub fn issue_command(&mut self, input: &str) -> Result<(), CommandBuildError> {
+        let (plane, command) = Command::parse(input, self.map.objects(), self.tick)?;
+
+        // Jets and props differ by case, but either can be typed in lowercase
         self.planes
             .iter_mut()
-            .find(|p| p.label() == &plane)
-            .unwrap()
+            .find(|p| p.label().eq_ignore_ascii_case(&plane))
+            .ok_or(CommandBuildError::UnknownPlane(plane))?
             .push_command(command);
-    }
+        self.recorder.record(self.tick, input.to_owned());
 
-    pub fn handle_event(&mut self, key_event: KeyEvent) -> bool {
-        match key_event {
-            KeyEvent {
-                code: KeyCode::Char('c'),
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            } => return true,
-            KeyEvent {
-                code: KeyCode::Backspace,
-                ..
-            }
-            | KeyEvent {
-                code: KeyCode::Char('h'),
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            } => self.cur_command.pop(),
-            KeyEvent {
-                code: KeyCode::Char(ch),
-                modifiers: KeyModifiers::NONE,
-                ..
-            } => self.cur_command.push(ch),
-            KeyEvent {
-                code: KeyCode::Esc, ..
-            } => self.cur_command.clear(),
-            KeyEvent {
-                code: KeyCode::Enter,
-                ..
-            } => self.build_command(),
-            _ => (),
-        }
-
-        false
+        Ok(())
     }
 
     pub fn commands(&self) -> Vec<(&Command, char)> {
@@ -113,3 +343,428 @@ impl App {
         commands
     }
 }
+
+// Planes enter one step in from an exit, facing away from it, so a prop
+// waiting on its first move isn't caught on the border. They take off from an
+// airport along its runway
+fn entry(origin: &Object) -> (Point, Direction, u8, u8) {
+    match origin {
+        Object::Exit {
+            position,
+            direction,
+            ..
+        } => {
+            let direction = direction.opposite();
+            (
+                position.step(&direction),
+                direction,
+                ENTRY_ALTITUDE,
+                ENTRY_ALTITUDE,
+            )
+        }
+        Object::Airport {
+            position,
+            direction,
+            ..
+        } => (position.clone(), *direction, 0, DEPARTURE_ALTITUDE),
+        Object::Beacon { .. } => unreachable!(),
+    }
+}
+
+// Within a cell of each other, diagonals included, and a level of altitude
+fn in_collision_range(
+    position_a: &Point,
+    altitude_a: u8,
+    position_b: &Point,
+    altitude_b: u8,
+) -> bool {
+    let Point(x_a, y_a) = position_a;
+    let Point(x_b, y_b) = position_b;
+
+    x_a.abs_diff(*x_b) <= 1 && y_a.abs_diff(*y_b) <= 1 && altitude_a.abs_diff(altitude_b) <= 1
+}
+
+#[derive(Debug, PartialEq)]
+enum PlaneStatus {
+    Flying,
+    Arrived,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    fn fixture(objects: Value, spawn_chance: f32, jet_chance: f32) -> Map {
+        json!({
+            "width": 10,
+            "height": 8,
+            "tick_rate": 1.0,
+            "spawn_chance": spawn_chance,
+            "jet_chance": jet_chance,
+            "objects": objects,
+            "lines": [],
+        })
+        .to_string()
+        .parse()
+        .unwrap()
+    }
+
+    fn map() -> Map {
+        fixture(
+            json!([
+                { "Airport": { "position": [3, 3], "direction": "East" } },
+                { "Airport": { "position": [7, 5], "direction": "North" } },
+                { "Exit": { "position": [0, 4], "direction": "West" } },
+            ]),
+            0.1,
+            0.5,
+        )
+    }
+
+    type PlaneState = (char, AircraftClass, Point, Direction, u8, u16, Destination);
+
+    fn snapshot(app: &App) -> Vec<PlaneState> {
+        app.planes()
+            .iter()
+            .map(|p| {
+                (
+                    *p.label(),
+                    *p.class(),
+                    p.position().clone(),
+                    *p.direction(),
+                    *p.altitude(),
+                    *p.fuel(),
+                    *p.destination(),
+                )
+            })
+            .collect()
+    }
+
+    fn plane(position: Point, direction: Direction, altitude: u8, target_altitude: u8) -> Plane {
+        Plane::new(
+            position,
+            direction,
+            'a',
+            AircraftClass::Jet,
+            Destination::Airport(0),
+            altitude,
+            target_altitude,
+        )
+    }
+
+    #[test]
+    fn landing_at_destination() {
+        let plane = plane(Point(3, 3), Direction::East, 0, 0);
+
+        assert_eq!(App::resolve_plane(&map(), &plane), Ok(PlaneStatus::Arrived));
+    }
+
+    #[test]
+    fn landing_counts_toward_score() {
+        let mut app = App::from_map(map(), Some(0));
+        app.planes.push(plane(Point(3, 3), Direction::East, 0, 0));
+
+        app.resolve_planes();
+        assert!(app.planes().is_empty());
+        assert_eq!(app.planes_handled(), 1);
+        assert!(!app.is_over());
+    }
+
+    #[test]
+    fn landing_at_wrong_airport() {
+        let plane = plane(Point(7, 5), Direction::North, 0, 0);
+
+        assert_eq!(
+            App::resolve_plane(&map(), &plane),
+            Err(GameOver::WrongAirport('A', 1))
+        );
+    }
+
+    #[test]
+    fn landing_from_wrong_direction() {
+        let plane = plane(Point(3, 3), Direction::West, 0, 0);
+
+        assert_eq!(
+            App::resolve_plane(&map(), &plane),
+            Err(GameOver::WrongLandingDirection('A', 0))
+        );
+    }
+
+    #[test]
+    fn landing_away_from_airport() {
+        let plane = plane(Point(5, 5), Direction::East, 0, 0);
+
+        assert_eq!(
+            App::resolve_plane(&map(), &plane),
+            Err(GameOver::GroundCrash('A'))
+        );
+    }
+
+    #[test]
+    fn passing_over_airport() {
+        let plane = plane(Point(3, 3), Direction::East, 1, 0);
+
+        assert_eq!(App::resolve_plane(&map(), &plane), Ok(PlaneStatus::Flying));
+    }
+
+    #[test]
+    fn takeoff_along_runway() {
+        let map = map();
+        let mut plane = plane(Point(7, 5), Direction::North, 0, DEPARTURE_ALTITUDE);
+
+        assert_eq!(App::resolve_plane(&map, &plane), Ok(PlaneStatus::Flying));
+
+        plane.update(map.objects(), 1);
+        assert_eq!(plane.position(), &Point(7, 4));
+        assert_eq!(plane.direction(), &Direction::North);
+        assert_eq!(plane.altitude(), &1);
+        assert_eq!(App::resolve_plane(&map, &plane), Ok(PlaneStatus::Flying));
+    }
+
+    // Flies a jet straight ahead until its tank is empty, starting far enough
+    // back to finish at `position`
+    fn last_move_to(position: Point, direction: Direction, destination: Destination) -> Plane {
+        let moves = AircraftClass::Jet.fuel_capacity();
+        let mut start = position.clone();
+        for _ in 0..moves {
+            start = start.step(&direction.opposite());
+        }
+
+        let altitude = match destination {
+            Destination::Airport(_) => 0,
+            Destination::Exit(_) => EXIT_ALTITUDE,
+        };
+        let mut plane = Plane::new(
+            start,
+            direction,
+            'a',
+            AircraftClass::Jet,
+            destination,
+            altitude,
+            altitude,
+        );
+        for tick in 1..=moves as u32 {
+            plane.update(&[], tick);
+        }
+        assert_eq!(plane.position(), &position);
+        assert!(plane.is_out_of_fuel());
+
+        plane
+    }
+
+    #[test]
+    fn collision() {
+        let mut app = App::from_map(map(), Some(0));
+        app.planes.push(plane(Point(5, 5), Direction::East, 7, 7));
+        app.planes.push(Plane::new(
+            Point(6, 6),
+            Direction::West,
+            'b',
+            AircraftClass::Prop,
+            Destination::Exit(0),
+            8,
+            8,
+        ));
+
+        app.resolve_planes();
+        assert_eq!(app.game_over(), Some(&GameOver::Collision('A', 'b')));
+    }
+
+    #[test]
+    fn leaving_away_from_an_exit() {
+        let plane = plane(Point(0, 2), Direction::West, EXIT_ALTITUDE, EXIT_ALTITUDE);
+
+        assert_eq!(
+            App::resolve_plane(&map(), &plane),
+            Err(GameOver::IllegalExit('A'))
+        );
+    }
+
+    #[test]
+    fn leaving_through_wrong_exit() {
+        let plane = plane(Point(0, 4), Direction::West, EXIT_ALTITUDE, EXIT_ALTITUDE);
+
+        assert_eq!(
+            App::resolve_plane(&map(), &plane),
+            Err(GameOver::IllegalExit('A'))
+        );
+    }
+
+    #[test]
+    fn leaving_at_wrong_altitude() {
+        let plane = Plane::new(
+            Point(0, 4),
+            Direction::West,
+            'a',
+            AircraftClass::Jet,
+            Destination::Exit(0),
+            7,
+            7,
+        );
+
+        assert_eq!(
+            App::resolve_plane(&map(), &plane),
+            Err(GameOver::WrongExitAltitude('A', 7))
+        );
+    }
+
+    #[test]
+    fn running_out_of_fuel() {
+        let plane = last_move_to(Point(5, 5), Direction::West, Destination::Exit(0));
+
+        assert_eq!(
+            App::resolve_plane(&map(), &plane),
+            Err(GameOver::OutOfFuel('A'))
+        );
+    }
+
+    #[test]
+    fn landing_on_last_move() {
+        let plane = last_move_to(Point(7, 5), Direction::North, Destination::Airport(1));
+
+        assert_eq!(App::resolve_plane(&map(), &plane), Ok(PlaneStatus::Arrived));
+    }
+
+    #[test]
+    fn leaving_on_last_move() {
+        let plane = last_move_to(Point(0, 4), Direction::West, Destination::Exit(0));
+
+        assert_eq!(App::resolve_plane(&map(), &plane), Ok(PlaneStatus::Arrived));
+    }
+
+    #[test]
+    fn props_enter_inside_the_board() {
+        let objects = json!([
+            { "Exit": { "position": [0, 4], "direction": "West" } },
+            { "Exit": { "position": [11, 4], "direction": "East" } },
+        ]);
+
+        for seed in 1..=3 {
+            let mut app = App::from_map(fixture(objects.clone(), 1.0, 0.0), Some(seed));
+
+            for _ in 0..4 {
+                app.update();
+                assert!(!matches!(app.game_over(), Some(GameOver::IllegalExit(_))));
+
+                for plane in app.planes() {
+                    let Point(x, y) = plane.position();
+                    assert!((1..=10).contains(x) && (1..=8).contains(y));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn no_spawn_in_collision_range() {
+        let objects = json!([
+            { "Exit": { "position": [0, 2], "direction": "West" } },
+            { "Exit": { "position": [11, 7], "direction": "East" } },
+        ]);
+        let mut app = App::from_map(fixture(objects, 1.0, 1.0), Some(0));
+
+        for _ in 0..8 {
+            app.update();
+            assert!(!matches!(app.game_over(), Some(GameOver::Collision(..))));
+        }
+        assert!(app.planes().len() > 1);
+    }
+
+    #[test]
+    fn no_spawn_after_game_over() {
+        let objects = json!([
+            { "Exit": { "position": [0, 2], "direction": "West" } },
+            { "Exit": { "position": [11, 7], "direction": "East" } },
+        ]);
+        let mut app = App::from_map(fixture(objects, 1.0, 1.0), Some(0));
+        app.planes.push(plane(Point(5, 5), Direction::East, 0, 0));
+
+        app.update();
+        assert_eq!(app.game_over(), Some(&GameOver::GroundCrash('A')));
+        assert!(app.planes().is_empty());
+    }
+
+    #[test]
+    fn direct_to_exit_leaves_through_it() {
+        let objects = json!([{ "Exit": { "position": [8, 0], "direction": "North" } }]);
+        let mut app = App::from_map(fixture(objects, 0.0, 1.0), Some(0));
+        app.planes.push(Plane::new(
+            Point(2, 5),
+            Direction::North,
+            'a',
+            AircraftClass::Jet,
+            Destination::Exit(0),
+            EXIT_ALTITUDE,
+            EXIT_ALTITUDE,
+        ));
+        app.issue_command("age0").unwrap();
+
+        for _ in 0..12 {
+            app.update();
+        }
+        assert_eq!(app.game_over(), None);
+        assert_eq!(app.planes_handled(), 1);
+    }
+
+    #[test]
+    fn replay_reproduces_the_game() {
+        let objects = json!([
+            { "Airport": { "position": [3, 3], "direction": "East" } },
+            { "Exit": { "position": [0, 4], "direction": "West" } },
+            { "Exit": { "position": [11, 4], "direction": "East" } },
+            { "Exit": { "position": [5, 0], "direction": "North" } },
+        ]);
+        let mut app = App::from_map(fixture(objects.clone(), 0.3, 0.5), Some(11));
+
+        for tick in 0..40 {
+            let labels: Vec<char> = app.planes().iter().map(|p| *p.label()).collect();
+            for label in labels {
+                let input = match tick % 3 {
+                    0 => format!("{label}c9"),
+                    1 => format!("{label}tr"),
+                    _ => format!("{label}tli02"),
+                };
+                app.issue_command(&input).unwrap();
+            }
+            app.update();
+        }
+        assert!((0..40).any(|tick| app.replay().commands_at(tick).next().is_some()));
+
+        let replay = app.replay().clone();
+        let mut replayed = App::from_map(fixture(objects, 0.3, 0.5), Some(*replay.seed()));
+        while replayed.tick() < app.tick() {
+            replayed.step_replay(&replay).unwrap();
+        }
+
+        assert!(!snapshot(&app).is_empty());
+        assert_eq!(snapshot(&replayed), snapshot(&app));
+        assert_eq!(replayed.tick(), app.tick());
+        assert_eq!(replayed.planes_handled(), app.planes_handled());
+        assert_eq!(replayed.game_over(), app.game_over());
+    }
+
+    #[test]
+    fn seed_determines_the_game() {
+        let objects = json!([
+            { "Airport": { "position": [3, 3], "direction": "East" } },
+            { "Exit": { "position": [0, 4], "direction": "West" } },
+            { "Exit": { "position": [11, 4], "direction": "East" } },
+            { "Exit": { "position": [5, 0], "direction": "North" } },
+        ]);
+        let run = |seed: u64| -> Vec<Vec<PlaneState>> {
+            let mut app = App::from_map(fixture(objects.clone(), 0.3, 0.5), Some(seed));
+            (0..30)
+                .map(|_| {
+                    app.update();
+                    snapshot(&app)
+                })
+                .collect()
+        };
+
+        let game = run(3);
+        assert!(game.iter().any(|planes| !planes.is_empty()));
+        assert_eq!(run(3), game);
+        assert_ne!(run(4), game);
+    }
+}
@@ -1,24 +1,55 @@
+use std::cmp::Ordering;
+
 use super::{
-    command::Command,
+    command::{Command, CommandType, Waypoint},
+    object::Object,
     util::{Direction, Point},
 };
 
+pub const MAX_ALTITUDE: u8 = 9;
+// In 45 degree steps, so at most a right angle per move
+const MAX_TURN_STEPS: i8 = 2;
+
 #[derive(Debug)]
 pub struct Plane {
     label: char,
+    class: AircraftClass,
     position: Point,
     direction: Direction,
+    target_direction: Direction,
+    circling: Option<bool>,
+    direct_to: Option<Waypoint>,
+    altitude: u8,
+    target_altitude: u8,
+    fuel: u16,
     mark_status: MarkStatus,
+    destination: Destination,
     commands: Vec<Command>,
 }
 
 impl Plane {
-    pub fn new(position: Point, direction: Direction, label: char) -> Self {
+    pub(crate) fn new(
+        position: Point,
+        direction: Direction,
+        label: char,
+        class: AircraftClass,
+        destination: Destination,
+        altitude: u8,
+        target_altitude: u8,
+    ) -> Self {
         Self {
-            label,
+            label: class.label(label),
+            class,
             position,
             direction,
+            target_direction: direction,
+            circling: None,
+            direct_to: None,
+            altitude,
+            target_altitude,
+            fuel: class.fuel_capacity(),
             mark_status: MarkStatus::Marked,
+            destination,
             commands: Vec::new(),
         }
     }
@@ -27,18 +58,235 @@ impl Plane {
         &self.label
     }
 
+    pub fn class(&self) -> &AircraftClass {
+        &self.class
+    }
+
+    pub fn position(&self) -> &Point {
+        &self.position
+    }
+
+    pub fn direction(&self) -> &Direction {
+        &self.direction
+    }
+
+    pub fn target_direction(&self) -> &Direction {
+        &self.target_direction
+    }
+
+    // Whether the plane is holding, and if so in which sense
+    pub fn circling(&self) -> Option<bool> {
+        self.circling
+    }
+
+    pub fn direct_to(&self) -> Option<&Waypoint> {
+        self.direct_to.as_ref()
+    }
+
+    pub fn altitude(&self) -> &u8 {
+        &self.altitude
+    }
+
+    pub fn target_altitude(&self) -> &u8 {
+        &self.target_altitude
+    }
+
+    pub fn fuel(&self) -> &u16 {
+        &self.fuel
+    }
+
+    pub fn is_out_of_fuel(&self) -> bool {
+        self.fuel == 0
+    }
+
+    pub fn mark_status(&self) -> &MarkStatus {
+        &self.mark_status
+    }
+
+    pub fn destination(&self) -> &Destination {
+        &self.destination
+    }
+
     pub fn commands(&self) -> &[Command] {
         &self.commands
     }
 
-    pub fn push_command(&mut self, command: Command) {
+    pub(crate) fn push_command(&mut self, command: Command) {
         self.commands.push(command);
     }
+
+    pub(crate) fn update(&mut self, objects: &[Object], tick: u32) {
+        self.execute_commands(objects, tick);
+        if !tick.is_multiple_of(self.class.move_interval()) {
+            return;
+        }
+
+        if let Some(clockwise) = self.circling {
+            let steps = if clockwise {
+                MAX_TURN_STEPS
+            } else {
+                -MAX_TURN_STEPS
+            };
+            self.target_direction = self.direction.rotate(steps);
+        }
+
+        // Steer for the waypoint until the plane is over it
+        if let Some(waypoint) = self.direct_to {
+            let heading = objects
+                .iter()
+                .find(|o| waypoint.is_object(o))
+                .and_then(|o| match o {
+                    // Exits sit on the border, so line up on the cell just
+                    // inside first or the plane may leave beside the exit
+                    Object::Exit {
+                        position,
+                        direction,
+                        ..
+                    } => {
+                        let approach = position.step(&direction.opposite());
+                        Some(self.position.direction_to(&approach).unwrap_or(*direction))
+                    }
+                    _ => self.position.direction_to(o.position()),
+                });
+            match heading {
+                Some(direction) => self.target_direction = direction,
+                None => self.direct_to = None,
+            }
+        }
+
+        let steps = self.direction.steps_to(&self.target_direction);
+        self.direction = self
+            .direction
+            .rotate(steps.clamp(-MAX_TURN_STEPS, MAX_TURN_STEPS));
+        self.position = self.position.step(&self.direction);
+
+        match self.altitude.cmp(&self.target_altitude) {
+            Ordering::Less => self.altitude += 1,
+            Ordering::Greater => self.altitude -= 1,
+            Ordering::Equal => (),
+        }
+
+        self.fuel = self.fuel.saturating_sub(1);
+    }
+
+    fn execute_commands(&mut self, objects: &[Object], tick: u32) {
+        let commands = std::mem::take(&mut self.commands);
+
+        for command in commands {
+            if !command.is_due(&self.position, objects, tick) {
+                self.commands.push(command);
+                continue;
+            }
+
+            self.apply(command.command_type());
+        }
+    }
+
+    fn apply(&mut self, command_type: &CommandType) {
+        match command_type {
+            CommandType::Turn(direction) => {
+                self.circling = None;
+                self.direct_to = None;
+                self.target_direction = *direction;
+            }
+            CommandType::TurnLeft(degrees) => {
+                self.circling = None;
+                self.direct_to = None;
+                self.target_direction = self.target_direction.rotate(-((*degrees / 45) as i8));
+            }
+            CommandType::TurnRight(degrees) => {
+                self.circling = None;
+                self.direct_to = None;
+                self.target_direction = self.target_direction.rotate((*degrees / 45) as i8);
+            }
+            CommandType::Circle { clockwise } => {
+                self.direct_to = None;
+                self.circling = Some(*clockwise);
+            }
+            CommandType::DirectTo(waypoint) => {
+                self.circling = None;
+                self.direct_to = Some(*waypoint);
+            }
+            CommandType::ChangeMark(mark_status) => self.mark_status = *mark_status,
+            CommandType::Climb(altitude) | CommandType::Dive(altitude) => {
+                self.target_altitude = *altitude
+            }
+        }
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AircraftClass {
+    Jet,
+    Prop,
+}
+
+impl AircraftClass {
+    // Number of ticks between moves
+    pub fn move_interval(&self) -> u32 {
+        match self {
+            AircraftClass::Jet => 1,
+            AircraftClass::Prop => 2,
+        }
+    }
+
+    // Measured in moves, so a prop stays in the air for twice as many ticks
+    pub fn fuel_capacity(&self) -> u16 {
+        match self {
+            AircraftClass::Jet => 80,
+            AircraftClass::Prop => 50,
+        }
+    }
+
+    pub fn label(&self, label: char) -> char {
+        match self {
+            AircraftClass::Jet => label.to_ascii_uppercase(),
+            AircraftClass::Prop => label.to_ascii_lowercase(),
+        }
+    }
+}
+
+impl std::fmt::Display for AircraftClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AircraftClass::Jet => write!(f, "jet"),
+            AircraftClass::Prop => write!(f, "prop"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum MarkStatus {
     Marked,
     Unmarked,
     Ignored,
 }
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Destination {
+    Airport(u8),
+    Exit(u8),
+}
+
+impl Destination {
+    pub fn from_object(object: &Object) -> Option<Self> {
+        match object {
+            Object::Airport { label, .. } => Some(Destination::Airport((*label)?)),
+            Object::Exit { label, .. } => Some(Destination::Exit((*label)?)),
+            Object::Beacon { .. } => None,
+        }
+    }
+
+    pub fn is_object(&self, object: &Object) -> bool {
+        Destination::from_object(object).as_ref() == Some(self)
+    }
+}
+
+impl std::fmt::Display for Destination {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Destination::Airport(label) => write!(f, "airport {label}"),
+            Destination::Exit(label) => write!(f, "exit {label}"),
+        }
+    }
+}
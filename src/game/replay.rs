@@ -0,0 +1,109 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{command::CommandBuildError, map::MapLoadError};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    map: PathBuf,
+    map_hash: u64,
+    seed: u64,
+    commands: Vec<RecordedCommand>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedCommand {
+    tick: u32,
+    input: String,
+}
+
+impl Replay {
+    pub(crate) fn new(map: PathBuf, map_hash: u64, seed: u64) -> Self {
+        Self {
+            map,
+            map_hash,
+            seed,
+            commands: Vec::new(),
+        }
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn map(&self) -> &Path {
+        &self.map
+    }
+
+    pub fn map_hash(&self) -> &u64 {
+        &self.map_hash
+    }
+
+    pub fn seed(&self) -> &u64 {
+        &self.seed
+    }
+
+    pub(crate) fn record(&mut self, tick: u32, input: String) {
+        self.commands.push(RecordedCommand { tick, input });
+    }
+
+    pub fn commands_at(&self, tick: u32) -> impl Iterator<Item = &str> {
+        self.commands
+            .iter()
+            .filter(move |c| c.tick == tick)
+            .map(|c| c.input.as_str())
+    }
+}
+
+// FNV-1a, which unlike the std hashers is stable across Rust releases
+pub(crate) fn hash_map_file(contents: &[u8]) -> u64 {
+    contents.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Map(MapLoadError),
+    MapChanged(PathBuf),
+    Rejected {
+        tick: u32,
+        input: String,
+        error: CommandBuildError,
+    },
+}
+
+impl std::fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayError::Map(e) => write!(f, "{e}"),
+            ReplayError::MapChanged(path) => write!(
+                f,
+                "{} has changed since the replay was recorded",
+                path.display()
+            ),
+            ReplayError::Rejected { tick, input, error } => write!(
+                f,
+                "recorded command '{input}' was rejected at tick {tick}: {error}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReplayError::Map(e) => Some(e),
+            ReplayError::MapChanged(_) => None,
+            ReplayError::Rejected { error, .. } => Some(error),
+        }
+    }
+}
@@ -1,11 +1,37 @@
-use serde::Deserialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq, Clone)]
 pub struct Point(pub u16, pub u16);
 
-pub const DIRECTION_CHARS: [char; 8] = ['q', 'w', 'e', 'a', 'd', 'z', 'x', 'c'];
+impl Point {
+    pub fn step(&self, direction: &Direction) -> Point {
+        let (dx, dy) = direction.offset();
 
-#[derive(Debug, Deserialize)]
+        Point(
+            self.0.saturating_add_signed(dx),
+            self.1.saturating_add_signed(dy),
+        )
+    }
+
+    // The nearest of the eight directions toward `other`, if it is elsewhere
+    pub fn direction_to(&self, other: &Point) -> Option<Direction> {
+        if self == other {
+            return None;
+        }
+
+        let dx = other.0 as f32 - self.0 as f32;
+        let dy = other.1 as f32 - self.1 as f32;
+        // Measured clockwise from north, which is toward negative y
+        let angle = dx.atan2(-dy);
+
+        Some(Direction::from_index(
+            (angle / std::f32::consts::FRAC_PI_4).round() as i8,
+        ))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq, Clone, Copy)]
 pub enum Direction {
     North,
     Northeast,
@@ -17,6 +43,77 @@ pub enum Direction {
     Northwest,
 }
 
+impl Direction {
+    pub fn offset(&self) -> (i16, i16) {
+        match self {
+            Direction::North => (0, -1),
+            Direction::Northeast => (1, -1),
+            Direction::East => (1, 0),
+            Direction::Southeast => (1, 1),
+            Direction::South => (0, 1),
+            Direction::Southwest => (-1, 1),
+            Direction::West => (-1, 0),
+            Direction::Northwest => (-1, -1),
+        }
+    }
+
+    // Clockwise from north, in 45 degree steps
+    fn index(&self) -> i8 {
+        match self {
+            Direction::North => 0,
+            Direction::Northeast => 1,
+            Direction::East => 2,
+            Direction::Southeast => 3,
+            Direction::South => 4,
+            Direction::Southwest => 5,
+            Direction::West => 6,
+            Direction::Northwest => 7,
+        }
+    }
+
+    fn from_index(index: i8) -> Direction {
+        match index.rem_euclid(8) {
+            0 => Direction::North,
+            1 => Direction::Northeast,
+            2 => Direction::East,
+            3 => Direction::Southeast,
+            4 => Direction::South,
+            5 => Direction::Southwest,
+            6 => Direction::West,
+            _ => Direction::Northwest,
+        }
+    }
+
+    // Positive steps turn clockwise
+    pub fn rotate(&self, steps: i8) -> Direction {
+        Direction::from_index(self.index() + steps)
+    }
+
+    // Shortest rotation onto `other`, turning clockwise when it is directly
+    // behind
+    pub fn steps_to(&self, other: &Direction) -> i8 {
+        let steps = (other.index() - self.index()).rem_euclid(8);
+        if steps > 4 {
+            steps - 8
+        } else {
+            steps
+        }
+    }
+
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::Northeast => Direction::Southwest,
+            Direction::East => Direction::West,
+            Direction::Southeast => Direction::Northwest,
+            Direction::South => Direction::North,
+            Direction::Southwest => Direction::Northeast,
+            Direction::West => Direction::East,
+            Direction::Northwest => Direction::Southeast,
+        }
+    }
+}
+
 impl std::fmt::Display for Direction {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let str = match self {
@@ -52,3 +149,217 @@ impl TryFrom<char> for Direction {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{
+        command::Command,
+        object::Object,
+        plane::{AircraftClass, Destination, Plane},
+    };
+
+    fn jet(direction: Direction) -> Plane {
+        Plane::new(
+            Point(5, 5),
+            direction,
+            'a',
+            AircraftClass::Jet,
+            Destination::Exit(0),
+            7,
+            7,
+        )
+    }
+
+    fn issue(plane: &mut Plane, objects: &[Object], input: &str) {
+        let (_, command) = Command::parse(input, objects, 0).unwrap();
+        plane.push_command(command);
+    }
+
+    #[test]
+    fn steps_to_takes_the_shortest_rotation() {
+        let cases = [
+            (Direction::North, Direction::North, 0),
+            (Direction::North, Direction::Northeast, 1),
+            (Direction::North, Direction::East, 2),
+            (Direction::North, Direction::Southeast, 3),
+            (Direction::North, Direction::Southwest, -3),
+            (Direction::North, Direction::West, -2),
+            (Direction::North, Direction::Northwest, -1),
+            (Direction::West, Direction::North, 2),
+            (Direction::Northeast, Direction::Northwest, -2),
+        ];
+
+        for (from, to, steps) in cases {
+            assert_eq!(from.steps_to(&to), steps, "{from} to {to}");
+        }
+    }
+
+    #[test]
+    fn steps_to_turns_clockwise_when_directly_behind() {
+        assert_eq!(Direction::North.steps_to(&Direction::South), 4);
+        assert_eq!(Direction::South.steps_to(&Direction::North), 4);
+        assert_eq!(Direction::East.steps_to(&Direction::West), 4);
+        assert_eq!(Direction::Northwest.steps_to(&Direction::Southeast), 4);
+    }
+
+    #[test]
+    fn turns_are_capped_at_a_right_angle_per_move() {
+        let mut plane = jet(Direction::North);
+        issue(&mut plane, &[], "atx");
+
+        plane.update(&[], 1);
+        assert_eq!(plane.direction(), &Direction::East);
+        assert_eq!(plane.target_direction(), &Direction::South);
+
+        plane.update(&[], 2);
+        assert_eq!(plane.direction(), &Direction::South);
+
+        let mut plane = jet(Direction::East);
+        issue(&mut plane, &[], "atq");
+
+        plane.update(&[], 1);
+        assert_eq!(plane.direction(), &Direction::North);
+
+        plane.update(&[], 2);
+        assert_eq!(plane.direction(), &Direction::Northwest);
+    }
+
+    #[test]
+    fn relative_turns_build_on_a_pending_turn() {
+        let mut plane = jet(Direction::North);
+        issue(&mut plane, &[], "atd");
+        issue(&mut plane, &[], "atR");
+
+        plane.update(&[], 1);
+        assert_eq!(plane.target_direction(), &Direction::South);
+        assert_eq!(plane.direction(), &Direction::East);
+
+        plane.update(&[], 2);
+        assert_eq!(plane.direction(), &Direction::South);
+
+        let mut plane = jet(Direction::North);
+        issue(&mut plane, &[], "atd");
+        issue(&mut plane, &[], "atl");
+
+        plane.update(&[], 1);
+        assert_eq!(plane.target_direction(), &Direction::Northeast);
+        assert_eq!(plane.direction(), &Direction::Northeast);
+    }
+
+    #[test]
+    fn direction_to_rounds_to_the_nearest_octant() {
+        let from = Point(5, 5);
+        let cases = [
+            (Point(5, 2), Direction::North),
+            (Point(8, 2), Direction::Northeast),
+            (Point(8, 5), Direction::East),
+            (Point(8, 8), Direction::Southeast),
+            (Point(5, 8), Direction::South),
+            (Point(2, 8), Direction::Southwest),
+            (Point(2, 5), Direction::West),
+            (Point(2, 2), Direction::Northwest),
+            (Point(6, 1), Direction::North),
+            (Point(7, 4), Direction::Northeast),
+            (Point(9, 4), Direction::East),
+            (Point(4, 9), Direction::South),
+            (Point(1, 6), Direction::West),
+        ];
+
+        for (to, direction) in cases {
+            assert_eq!(from.direction_to(&to), Some(direction), "{to:?}");
+        }
+        assert_eq!(from.direction_to(&from), None);
+    }
+
+    #[test]
+    fn direct_to_reaches_the_waypoint() {
+        let beacon = Point(9, 2);
+        let objects = [Object::Beacon {
+            position: beacon.clone(),
+            label: Some(0),
+        }];
+        let mut plane = jet(Direction::West);
+        issue(&mut plane, &objects, "agb0");
+
+        let mut tick = 0;
+        while plane.position() != &beacon {
+            tick += 1;
+            assert!(tick <= 12, "never reached the beacon");
+            plane.update(&objects, tick);
+        }
+        assert!(plane.direct_to().is_some());
+
+        plane.update(&objects, tick + 1);
+        assert!(plane.direct_to().is_none());
+    }
+
+    #[test]
+    fn commands_leave_the_queue_when_they_fire() {
+        let objects = [Object::Beacon {
+            position: Point(5, 3),
+            label: Some(0),
+        }];
+        let mut plane = jet(Direction::North);
+        issue(&mut plane, &objects, "ac2ab0");
+        issue(&mut plane, &objects, "ac3i02");
+        issue(&mut plane, &objects, "ac5");
+
+        plane.update(&objects, 1);
+        assert_eq!(plane.commands().len(), 2);
+        assert_eq!(plane.target_altitude(), &5);
+
+        plane.update(&objects, 2);
+        assert_eq!(plane.commands().len(), 1);
+        assert_eq!(plane.target_altitude(), &3);
+        assert_eq!(plane.position(), &Point(5, 3));
+
+        plane.update(&objects, 3);
+        assert!(plane.commands().is_empty());
+        assert_eq!(plane.target_altitude(), &2);
+    }
+
+    #[test]
+    fn holding_pattern_returns_to_its_start() {
+        for (input, clockwise, first) in [("ahr", true, Point(6, 5)), ("ahl", false, Point(4, 5))] {
+            let mut plane = jet(Direction::North);
+            issue(&mut plane, &[], input);
+
+            plane.update(&[], 1);
+            assert_eq!(plane.position(), &first);
+            for tick in 2..=4 {
+                plane.update(&[], tick);
+            }
+            assert_eq!(plane.position(), &Point(5, 5));
+            assert_eq!(plane.direction(), &Direction::North);
+            assert_eq!(plane.circling(), Some(clockwise));
+        }
+    }
+
+    #[test]
+    fn later_commands_cancel_the_holding_pattern() {
+        let objects = [Object::Beacon {
+            position: Point(5, 1),
+            label: Some(0),
+        }];
+
+        // Circling would carry on south from (6, 5)
+        let cases = [
+            ("atw", Point(6, 4)),
+            ("atl", Point(7, 4)),
+            ("atL", Point(6, 4)),
+            ("agb0", Point(6, 4)),
+        ];
+
+        for (input, position) in cases {
+            let mut plane = jet(Direction::North);
+            issue(&mut plane, &objects, "ahr");
+            plane.update(&objects, 1);
+
+            issue(&mut plane, &objects, input);
+            plane.update(&objects, 2);
+            assert_eq!(plane.circling(), None, "{input}");
+            assert_eq!(plane.position(), &position, "{input}");
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use std::io::{self, BufRead, Write};
+
+use serde::Serialize;
+
+use zen_atc::{App, Direction, Point};
+
+const TICK_TOKEN: &str = "tick";
+
+#[derive(Debug, Serialize)]
+struct WorldState<'a> {
+    tick: u32,
+    planes_handled: u32,
+    planes: Vec<PlaneState<'a>>,
+    rejected: Vec<RejectedCommand>,
+    game_over: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct PlaneState<'a> {
+    label: char,
+    class: String,
+    position: &'a Point,
+    direction: &'a Direction,
+    target_direction: &'a Direction,
+    circling: Option<bool>,
+    direct_to: Option<String>,
+    altitude: u8,
+    target_altitude: u8,
+    fuel: u16,
+    destination: String,
+    commands: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct RejectedCommand {
+    input: String,
+    error: String,
+}
+
+// Every line of input is a list of whitespace separated commands in the same
+// syntax the command writer accepts. Unless `manual_tick` is set, the game
+// advances one tick after each line; otherwise it only advances on a `tick`
+// token. The world state is written as one JSON object per tick.
+pub fn run(
+    app: &mut App,
+    input: impl BufRead,
+    mut output: impl Write,
+    manual_tick: bool,
+) -> io::Result<()> {
+    let mut rejected = Vec::new();
+
+    for line in input.lines() {
+        let line = line?;
+
+        for token in line.split_whitespace() {
+            if token == TICK_TOKEN {
+                step(app, &mut output, &mut rejected)?;
+            } else if let Err(e) = app.issue_command(token) {
+                rejected.push(RejectedCommand {
+                    input: token.to_owned(),
+                    error: e.to_string(),
+                });
+            }
+
+            if app.is_over() {
+                return Ok(());
+            }
+        }
+
+        if !manual_tick {
+            step(app, &mut output, &mut rejected)?;
+        }
+
+        if app.is_over() {
+            return Ok(());
+        }
+    }
+
+    Ok(())
+}
+
+fn step(
+    app: &mut App,
+    output: &mut impl Write,
+    rejected: &mut Vec<RejectedCommand>,
+) -> io::Result<()> {
+    app.update();
+
+    let state = WorldState {
+        tick: app.tick(),
+        planes_handled: app.planes_handled(),
+        planes: app
+            .planes()
+            .iter()
+            .map(|plane| PlaneState {
+                label: *plane.label(),
+                class: plane.class().to_string(),
+                position: plane.position(),
+                direction: plane.direction(),
+                target_direction: plane.target_direction(),
+                circling: plane.circling(),
+                direct_to: plane.direct_to().map(|w| w.to_string()),
+                altitude: *plane.altitude(),
+                target_altitude: *plane.target_altitude(),
+                fuel: *plane.fuel(),
+                destination: plane.destination().to_string(),
+                commands: plane.commands().iter().map(|c| c.to_string()).collect(),
+            })
+            .collect(),
+        rejected: std::mem::take(rejected),
+        game_over: app.game_over().map(|g| g.to_string()),
+    };
+
+    serde_json::to_writer(&mut *output, &state)?;
+    writeln!(output)?;
+    output.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+    use zen_atc::{CommandBuildError, Map};
+
+    use super::*;
+
+    #[test]
+    fn rejected_commands_give_a_reason() {
+        let map: Map = include_str!("../maps/default.json").parse().unwrap();
+        let mut app = App::from_map(map, Some(0));
+        let mut output = Vec::new();
+
+        run(
+            &mut app,
+            "1ac3 ac3xyz atp ab zc3 zm\n".as_bytes(),
+            &mut output,
+            false,
+        )
+        .unwrap();
+
+        let state: Value = serde_json::from_slice(&output).unwrap();
+        let errors: Vec<&str> = state["rejected"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["error"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                CommandBuildError::UnknownPlane('1').to_string(),
+                CommandBuildError::Malformed("ac3xyz".to_owned()).to_string(),
+                CommandBuildError::BadDirection('p').to_string(),
+                CommandBuildError::UnknownCommand('b').to_string(),
+                CommandBuildError::UnknownPlane('z').to_string(),
+                CommandBuildError::UnknownPlane('z').to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,16 @@
+//! The zen_atc simulator: load a [`Map`], step an [`App`] one tick at a time,
+//! issue commands in the same syntax the terminal game accepts and read back
+//! the planes, objects and game state.
+
+mod game;
+
+pub use game::{
+    command::{Command, CommandBuildError, CommandCondition, CommandType, Waypoint},
+    game_over::GameOver,
+    map::{Line, LineError, Map, MapError, MapLoadError, MapLoadErrorKind, ObjectPlacementError},
+    object::Object,
+    plane::{AircraftClass, Destination, MarkStatus, Plane, MAX_ALTITUDE},
+    replay::{Replay, ReplayError},
+    util::{Direction, Point},
+    App,
+};
@@ -1,28 +1,27 @@
-#![allow(dead_code)]
-
-use std::{
-    io,
-    path::PathBuf,
-    time::{Duration, Instant},
-};
+use std::{io, path::PathBuf, time::Instant};
 
 use clap::{Parser, Subcommand};
 use crossterm::{
-    event::{poll, read, Event},
+    event::{poll, read, Event, KeyCode, KeyEvent, KeyModifiers},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use game::App;
+use prompt::Prompt;
 use ratatui::{
     backend::{Backend, CrosstermBackend},
     Terminal,
 };
+use scores::Score;
+use zen_atc::{App, Map, Object, Replay};
 
-mod game;
+mod headless;
+mod paths;
+mod prompt;
+mod scores;
 mod ui;
 
-// todo: add real default path
-const DEFAULT_MAP_PATH: &str = "test/map.json";
+const GAME_SPEEDS: [f32; 4] = [0.5, 1.0, 2.0, 4.0];
+const REPLAY_SPEEDS: [f32; 6] = [0.25, 0.5, 1.0, 2.0, 4.0, 8.0];
 type BackendType = CrosstermBackend<io::Stdout>;
 
 #[derive(Debug, Parser)]
@@ -34,16 +33,54 @@ struct Args {
 
 #[derive(Debug, Subcommand)]
 enum Command {
-    Play { path: Option<PathBuf> },
+    Play {
+        map: Option<PathBuf>,
+        #[arg(short, long)]
+        name: Option<String>,
+        #[arg(short, long)]
+        seed: Option<u64>,
+        #[arg(short, long)]
+        record: Option<PathBuf>,
+    },
+    Replay {
+        file: PathBuf,
+        #[arg(short, long)]
+        map: Option<PathBuf>,
+    },
+    Headless {
+        map: PathBuf,
+        #[arg(short, long)]
+        seed: Option<u64>,
+        #[arg(long)]
+        manual_tick: bool,
+    },
     List,
-    Scores,
+    Scores {
+        #[arg(short, long)]
+        map: Option<String>,
+    },
+    Schema,
 }
 
 fn main() {
     let args = Args::parse();
 
     match args.command {
-        Command::Play { path } => {
+        Command::Play {
+            map,
+            name,
+            seed,
+            record,
+        } => {
+            let map = map.unwrap_or(PathBuf::from(paths::DEFAULT_MAP));
+            let path = match paths::resolve_map(&map) {
+                Some(p) => p,
+                None => {
+                    eprintln!("No map named {} was found", map.display());
+                    return;
+                }
+            };
+
             let mut terminal = match instantiate_terminal() {
                 Ok(t) => t,
                 Err(e) => {
@@ -52,17 +89,9 @@ fn main() {
                 }
             };
 
-            // ...
-            fn sleep(duration: Duration) {
-                let start = Instant::now();
-                while start.elapsed() < duration {}
-            }
             terminal.set_cursor(0, 0).unwrap();
 
-            let default_path = PathBuf::from(DEFAULT_MAP_PATH);
-            let path = path.unwrap_or(default_path);
-
-            let app = match App::new(path) {
+            let mut app = match App::new(path.clone(), seed) {
                 Ok(a) => a,
                 Err(e) => {
                     if let Err(e) = reset_terminal(terminal) {
@@ -73,7 +102,7 @@ fn main() {
                 }
             };
 
-            if let Err(e) = run_app(&mut terminal, app) {
+            if let Err(e) = run_app(&mut terminal, &mut app) {
                 if let Err(e) = reset_terminal(terminal) {
                     eprintln!("Error while resetting terminal: {e}");
                 }
@@ -84,9 +113,172 @@ fn main() {
             if let Err(e) = reset_terminal(terminal) {
                 eprintln!("Error while resetting terminal: {e}");
             }
+
+            if let Some(record) = record {
+                if let Err(e) = app.replay().save(&record) {
+                    eprintln!("Error while saving replay: {e}");
+                }
+            }
+
+            let reason = match app.game_over() {
+                Some(game_over) => {
+                    println!("Game over: {game_over}");
+                    println!("Seed: {}", app.seed());
+                    game_over.to_string()
+                }
+                None => "Quit".to_owned(),
+            };
+
+            let player = name
+                .or_else(|| std::env::var("USER").ok())
+                .unwrap_or("unknown".to_owned());
+            let map_name = path
+                .file_stem()
+                .map_or("unknown".to_owned(), |s| s.to_string_lossy().into_owned());
+            let score = Score::new(player, map_name, app.planes_handled(), app.tick(), reason);
+            if let Err(e) = scores::record(&score) {
+                eprintln!("Error while recording score: {e}");
+            }
+        }
+        Command::Replay { file, map } => {
+            let replay = match Replay::load(&file) {
+                Ok(r) => r,
+                Err(e) => {
+                    eprintln!("Error while loading replay: {e}");
+                    return;
+                }
+            };
+
+            let mut app = match App::from_replay(&replay, map) {
+                Ok(a) => a,
+                Err(e) => {
+                    eprintln!("Error while loading map: {e}");
+                    return;
+                }
+            };
+
+            let mut terminal = match instantiate_terminal() {
+                Ok(t) => t,
+                Err(e) => {
+                    eprintln!("Error while instantiating terminal: {e}");
+                    return;
+                }
+            };
+
+            let result = run_replay(&mut terminal, &mut app, &replay);
+            if let Err(e) = reset_terminal(terminal) {
+                eprintln!("Error while resetting terminal: {e}");
+            }
+            if let Err(e) = result {
+                eprintln!("Error while running replay: {e}");
+            }
         }
-        Command::List => {}
-        Command::Scores => {}
+        Command::Headless {
+            map,
+            seed,
+            manual_tick,
+        } => {
+            let path = match paths::resolve_map(&map) {
+                Some(p) => p,
+                None => {
+                    eprintln!("No map named {} was found", map.display());
+                    return;
+                }
+            };
+
+            let mut app = match App::new(path, seed) {
+                Ok(a) => a,
+                Err(e) => {
+                    eprintln!("Error while loading map: {e}");
+                    return;
+                }
+            };
+
+            if let Err(e) = headless::run(&mut app, io::stdin().lock(), io::stdout(), manual_tick) {
+                eprintln!("Error while running headless game: {e}");
+            }
+        }
+        Command::List => list_maps(),
+        Command::Schema => match serde_json::to_string_pretty(&Map::schema()) {
+            Ok(schema) => println!("{schema}"),
+            Err(e) => eprintln!("Error while generating schema: {e}"),
+        },
+        Command::Scores { map } => print_scores(map.as_deref()),
+    }
+}
+
+fn list_maps() {
+    let maps = paths::find_maps();
+    if maps.is_empty() {
+        let dirs: Vec<String> = paths::map_dirs()
+            .iter()
+            .map(|d| d.display().to_string())
+            .collect();
+        println!("No maps found in {}", dirs.join(", "));
+        return;
+    }
+
+    println!(
+        "{:<16} {:>7} {:>8} {:>7} {:>5} {:>9}  Status",
+        "Name", "Size", "Airports", "Beacons", "Exits", "Tick rate"
+    );
+
+    for (name, path) in maps {
+        let map = match Map::read(&path) {
+            Ok(m) => m,
+            Err(e) => {
+                println!("{name:<16} {e}");
+                continue;
+            }
+        };
+
+        let count =
+            |is_type: fn(&Object) -> bool| map.objects().iter().filter(|o| is_type(o)).count();
+        let size = format!("{}x{}", map.width(), map.height());
+        let status = match map.validate() {
+            Ok(()) => "valid".to_owned(),
+            Err(e) => format!("invalid: {e}"),
+        };
+
+        println!(
+            "{name:<16} {size:>7} {:>8} {:>7} {:>5} {:>8}s  {status}",
+            count(Object::is_airport),
+            count(Object::is_beacon),
+            count(Object::is_exit),
+            map.tick_rate(),
+        );
+    }
+}
+
+fn print_scores(map: Option<&str>) {
+    let scores = match scores::ranked(map) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Error while reading scores: {e}");
+            return;
+        }
+    };
+
+    if scores.is_empty() {
+        println!("No scores recorded yet");
+        return;
+    }
+
+    println!(
+        "{:>4} {:<12} {:<12} {:>6} {:>6} {:<10}  Reason",
+        "Rank", "Player", "Map", "Planes", "Ticks", "Date"
+    );
+    for (k, score) in scores.iter().enumerate() {
+        println!(
+            "{:>4} {:<12} {:<12} {:>6} {:>6} {:<10}  {}",
+            k + 1,
+            score.player,
+            score.map,
+            score.planes_handled,
+            score.ticks,
+            score.date(),
+            score.reason
+        );
     }
 }
 
@@ -109,20 +301,50 @@ fn reset_terminal<B: Backend + io::Write>(mut terminal: Terminal<B>) -> Result<(
 
 fn run_app<B: Backend + io::Write>(
     terminal: &mut Terminal<B>,
-    mut app: App,
+    app: &mut App,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let mut last_tick = Instant::now();
-    let tick_duration = app.tick_duration();
+    let mut speed = GAME_SPEEDS.iter().position(|s| *s == 1.0).unwrap();
+    let mut paused_at: Option<Instant> = None;
+    let mut prompt = Prompt::default();
 
     loop {
-        terminal.draw(|f| ui::ui(f, &app))?;
+        let header = match paused_at {
+            Some(_) => "Paused (space to resume)".to_owned(),
+            None => format!(
+                "Speed: {}x (F1-F4 to change, space to pause)",
+                GAME_SPEEDS[speed]
+            ),
+        };
+        terminal.draw(|f| ui::ui(f, app, &prompt, header, paused_at.is_some()))?;
+
+        if app.is_over() {
+            while !matches!(read()?, Event::Key(_)) {}
+            break;
+        }
 
-        let poll_time = tick_duration - last_tick.elapsed();
-        if poll(poll_time)? {
+        let tick_duration = app.tick_duration().div_f32(GAME_SPEEDS[speed]);
+        let poll_time = tick_duration.saturating_sub(last_tick.elapsed());
+        if paused_at.is_some() || poll(poll_time)? {
             if let Event::Key(key_event) = read()? {
-                if app.handle_event(key_event) {
-                    break;
-                };
+                match (key_event.code, paused_at) {
+                    (KeyCode::Char(' '), Some(time)) => {
+                        // Resume with whatever was left of the interrupted tick
+                        last_tick += time.elapsed();
+                        paused_at = None;
+                    }
+                    (KeyCode::Char(' '), None) => paused_at = Some(Instant::now()),
+                    (KeyCode::F(n @ 1..=4), _) => speed = n as usize - 1,
+                    (KeyCode::Char('c'), _) if key_event.modifiers == KeyModifiers::CONTROL => {
+                        break
+                    }
+                    (_, Some(_)) => (),
+                    (_, None) => {
+                        if handle_key_event(app, &mut prompt, key_event) {
+                            break;
+                        }
+                    }
+                }
             }
 
             continue;
@@ -134,3 +356,93 @@ fn run_app<B: Backend + io::Write>(
 
     Ok(())
 }
+
+// Returns true when the player asks to quit
+fn handle_key_event(app: &mut App, prompt: &mut Prompt, key_event: KeyEvent) -> bool {
+    match key_event {
+        KeyEvent {
+            code: KeyCode::Char('c'),
+            modifiers: KeyModifiers::CONTROL,
+            ..
+        } => return true,
+        KeyEvent {
+            code: KeyCode::Backspace,
+            ..
+        }
+        | KeyEvent {
+            code: KeyCode::Char('h'),
+            modifiers: KeyModifiers::CONTROL,
+            ..
+        } => prompt.pop(),
+        KeyEvent {
+            code: KeyCode::Char(ch),
+            modifiers: KeyModifiers::NONE | KeyModifiers::SHIFT,
+            ..
+        } => prompt.push(ch),
+        KeyEvent {
+            code: KeyCode::Esc, ..
+        } => prompt.clear(),
+        KeyEvent {
+            code: KeyCode::Enter,
+            ..
+        } => prompt.submit(app),
+        _ => (),
+    }
+
+    false
+}
+
+fn run_replay<B: Backend + io::Write>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    replay: &Replay,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut speed = REPLAY_SPEEDS.iter().position(|s| *s == 1.0).unwrap();
+    let mut paused = false;
+    let mut last_tick = Instant::now();
+    let mut diverged = None;
+
+    loop {
+        let mut status = format!(
+            "tick {} | {}x{} | space: pause, n: step, +/-: speed, q: quit",
+            app.tick(),
+            REPLAY_SPEEDS[speed],
+            if paused { " (paused)" } else { "" }
+        );
+        if let Some(e) = &diverged {
+            status = format!("Replay diverged: {e} | {status}");
+        }
+        terminal.draw(|f| ui::replay_ui(f, app, status))?;
+
+        let tick_duration = app.tick_duration().div_f32(REPLAY_SPEEDS[speed]);
+        let poll_time = tick_duration.saturating_sub(last_tick.elapsed());
+        if paused || app.is_over() || poll(poll_time)? {
+            if let Event::Key(key_event) = read()? {
+                match key_event.code {
+                    KeyCode::Char('q') | KeyCode::Esc => break,
+                    KeyCode::Char('c') if key_event.modifiers == KeyModifiers::CONTROL => break,
+                    KeyCode::Char(' ') => paused = !paused,
+                    KeyCode::Char('n') if paused && !app.is_over() => {
+                        if let Err(e) = app.step_replay(replay) {
+                            diverged.get_or_insert(e);
+                        }
+                    }
+                    KeyCode::Char('+') => speed = (speed + 1).min(REPLAY_SPEEDS.len() - 1),
+                    KeyCode::Char('-') => speed = speed.saturating_sub(1),
+                    _ => (),
+                }
+            }
+
+            continue;
+        }
+
+        // Stop where the game drifted from the recording so it can be seen
+        if let Err(e) = app.step_replay(replay) {
+            diverged.get_or_insert(e);
+            paused = true;
+        }
+        last_tick = Instant::now();
+    }
+
+    Ok(())
+}
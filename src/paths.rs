@@ -0,0 +1,112 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+const MAP_PATH_VAR: &str = "ZEN_ATC_MAP_PATH";
+const SYSTEM_MAP_DIRS: [&str; 2] = ["/usr/local/share/zen_atc/maps", "/usr/share/zen_atc/maps"];
+const MAP_EXTENSION: &str = "json";
+pub const DEFAULT_MAP: &str = "default";
+// Built into the binary so there is always something to play, even without a
+// system install
+const DEFAULT_MAP_TEXT: &str = include_str!("../maps/default.json");
+
+pub fn data_dir() -> Option<PathBuf> {
+    let data_home = env::var_os("XDG_DATA_HOME")
+        .filter(|d| !d.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|h| PathBuf::from(h).join(".local/share")))?;
+
+    Some(data_home.join("zen_atc"))
+}
+
+// Directories from the environment take precedence over the user's data dir,
+// which in turn takes precedence over the system-wide maps
+pub fn map_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = env::var_os(MAP_PATH_VAR)
+        .map(|paths| env::split_paths(&paths).collect())
+        .unwrap_or_default();
+
+    if let Some(data_dir) = data_dir() {
+        dirs.push(data_dir.join("maps"));
+    }
+    dirs.extend(SYSTEM_MAP_DIRS.iter().map(PathBuf::from));
+
+    dirs
+}
+
+pub fn find_maps() -> Vec<(String, PathBuf)> {
+    let mut maps: Vec<(String, PathBuf)> = Vec::new();
+
+    for dir in map_dirs() {
+        let mut entries: Vec<PathBuf> = match fs::read_dir(&dir) {
+            Ok(entries) => entries.filter_map(|e| Some(e.ok()?.path())).collect(),
+            Err(_) => continue,
+        };
+        entries.sort();
+
+        for path in entries {
+            let name = match map_name(&path) {
+                Some(n) => n,
+                None => continue,
+            };
+
+            if !maps.iter().any(|(n, _)| n == &name) {
+                maps.push((name, path));
+            }
+        }
+    }
+
+    // Games and replays refer to maps by path, so the built-in default is
+    // written out to the user's data dir the first time it is needed
+    if !maps.iter().any(|(n, _)| n == DEFAULT_MAP) {
+        if let Some(path) = data_dir().and_then(|d| install_default_map(&d.join("maps"))) {
+            maps.push((DEFAULT_MAP.to_owned(), path));
+        }
+    }
+
+    maps
+}
+
+pub fn resolve_map(map: &Path) -> Option<PathBuf> {
+    if map.is_file() {
+        return Some(map.to_owned());
+    }
+
+    let name = map.to_str()?;
+    find_maps()
+        .into_iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, path)| path)
+}
+
+fn install_default_map(dir: &Path) -> Option<PathBuf> {
+    let path = dir.join(DEFAULT_MAP).with_extension(MAP_EXTENSION);
+    fs::create_dir_all(dir).ok()?;
+    fs::write(&path, DEFAULT_MAP_TEXT).ok()?;
+
+    Some(path)
+}
+
+fn map_name(path: &Path) -> Option<String> {
+    if !path.is_file() || path.extension()? != MAP_EXTENSION {
+        return None;
+    }
+
+    Some(path.file_stem()?.to_str()?.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn installed_default_map_loads() {
+        let dir = env::temp_dir().join(format!("zen_atc_maps_{}", std::process::id()));
+        let path = install_default_map(&dir).unwrap();
+
+        assert_eq!(map_name(&path).as_deref(), Some(DEFAULT_MAP));
+        assert!(zen_atc::Map::load(&path).is_ok());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,294 @@
+use std::time::{Duration, Instant};
+
+use zen_atc::{App, CommandBuildError, Direction};
+
+const ERROR_DURATION: Duration = Duration::from_secs(3);
+
+// The command line under the radar. Keys are filtered as they're typed, and
+// a rejected command's reason stays up for a few seconds
+#[derive(Debug, Default)]
+pub struct Prompt {
+    writer: CommandWriter,
+    error: Option<(CommandBuildError, Instant)>,
+}
+
+impl Prompt {
+    pub fn error(&self) -> Option<&CommandBuildError> {
+        match &self.error {
+            Some((e, time)) if time.elapsed() < ERROR_DURATION => Some(e),
+            _ => None,
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.writer.to_string()
+    }
+
+    pub fn push(&mut self, input: char) {
+        self.error = None;
+        self.writer.push(input);
+    }
+
+    pub fn pop(&mut self) {
+        self.error = None;
+        self.writer.pop();
+    }
+
+    pub fn clear(&mut self) {
+        self.error = None;
+        self.writer.clear();
+    }
+
+    pub fn submit(&mut self, app: &mut App) {
+        let writer = std::mem::take(&mut self.writer);
+        self.error = None;
+
+        match app.issue_command(writer.input()) {
+            Ok(()) | Err(CommandBuildError::Empty) => (),
+            Err(e) => self.error = Some((e, Instant::now())),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CommandWriter {
+    cur_string: String,
+}
+
+impl CommandWriter {
+    fn new() -> Self {
+        Self {
+            cur_string: String::new(),
+        }
+    }
+
+    fn input(&self) -> &str {
+        &self.cur_string
+    }
+
+    fn push(&mut self, input: char) {
+        if self.cur_string.is_empty() {
+            if input.is_alphabetic() {
+                self.cur_string.push(input);
+            }
+            return;
+        }
+
+        // A direct-to command carries the waypoint's label before any
+        // condition, so everything after it sits one character later
+        let direct_to = self.cur_string.chars().nth(1) == Some('g');
+        let len = match (direct_to, self.cur_string.len()) {
+            (true, 3) => {
+                if input.is_ascii_digit() {
+                    self.cur_string.push(input)
+                }
+                return;
+            }
+            (true, len) if len > 3 => len - 1,
+            (_, len) => len,
+        };
+
+        let prev_char = self.cur_string.chars().last().unwrap();
+        match len {
+            1 => match input {
+                't' | 'c' | 'd' | 'h' | 'g' => self.cur_string.push(input),
+                'm' | 'u' | 'i' => {
+                    self.cur_string.push(input);
+                    self.cur_string.push('_');
+                }
+                _ => (),
+            },
+            2 => {
+                if matches!(
+                    (prev_char, input),
+                    (
+                        't',
+                        'q' | 'w' | 'e' | 'a' | 'd' | 'z' | 'x' | 'c' | 'l' | 'r' | 'L' | 'R'
+                    ) | ('c' | 'd', '0'..='9')
+                        | ('h', 'l' | 'r')
+                        | ('g', 'a' | 'b' | 'e')
+                ) {
+                    self.cur_string.push(input)
+                }
+            }
+            3 => {
+                if matches!(input, 'a' | 'i') {
+                    self.cur_string.push(input)
+                }
+            }
+            4 => {
+                if matches!((prev_char, input), ('i', '0'..='9') | ('a', 'a' | 'b')) {
+                    self.cur_string.push(input)
+                }
+            }
+            5 => {
+                if matches!((prev_char, input), ('a' | 'b' | '0'..='9', '0'..='9')) {
+                    self.cur_string.push(input)
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn pop(&mut self) {
+        if let Some('_') = self.cur_string.pop() {
+            self.cur_string.pop();
+        }
+    }
+
+    fn clear(&mut self) {
+        self.cur_string.clear();
+    }
+}
+
+impl Default for CommandWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Display for CommandWriter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut display_string = String::new();
+        let mut chars = self.cur_string.chars();
+
+        let plane = match chars.next() {
+            Some(p) => format!("{p}:"),
+            None => return Ok(()),
+        };
+        display_string.push_str(&plane);
+
+        let command_char = match chars.next() {
+            Some(ch) => ch,
+            None => return write!(f, "{display_string}"),
+        };
+        let command_type = match command_char {
+            'c' => " climb",
+            'd' => " dive",
+            't' => " turn",
+            'h' => " circle",
+            'g' => " fly to",
+            'm' => " mark",
+            'u' => " unmark",
+            'i' => " ignore",
+            x => panic!("Invalid command character encountered: {x}"),
+        };
+        display_string.push_str(command_type);
+
+        let command_arg = match (command_char, chars.next()) {
+            (_, Some('_')) => "".to_owned(),
+            ('h', Some('r')) => " clockwise".to_owned(),
+            ('h', Some('l')) => " counterclockwise".to_owned(),
+            ('g', Some('a')) => " airport".to_owned(),
+            ('g', Some('b')) => " beacon".to_owned(),
+            ('g', Some('e')) => " exit".to_owned(),
+            (_, Some(ch)) if is_direction(ch) => {
+                format!(" {}", Direction::try_from(ch).unwrap())
+            }
+            (_, Some('l')) => " left 45 degrees".to_owned(),
+            (_, Some('L')) => " left 90 degrees".to_owned(),
+            (_, Some('r')) => " right 45 degrees".to_owned(),
+            (_, Some('R')) => " right 90 degrees".to_owned(),
+            (_, Some(num)) if num.is_numeric() => format!(" {num}000 feet"),
+            (_, Some(x)) => panic!("Invalid command argument encountered: {x}"),
+            (_, None) => return write!(f, "{display_string}"),
+        };
+        display_string.push_str(&command_arg);
+
+        if command_char == 'g' {
+            match chars.next() {
+                Some(num) if num.is_numeric() => display_string.push_str(&format!(" {num}")),
+                Some(x) => panic!("Invalid waypoint label encountered: {x}"),
+                None => return write!(f, "{display_string}"),
+            }
+        }
+
+        let condition_type = match chars.next() {
+            Some('i') => " in",
+            Some('a') => " at",
+            Some(x) => panic!("Invalid condition type encountered: {x}"),
+            None => return write!(f, "{display_string}"),
+        };
+        display_string.push_str(condition_type);
+
+        let condition_arg_1 = match chars.next() {
+            Some('a') => " airport",
+            Some('b') => " beacon",
+            Some(tens) if tens.is_numeric() => {
+                return match chars.next().and_then(to_digit) {
+                    Some(ones) => write!(
+                        f,
+                        "{display_string} {} seconds",
+                        to_digit(tens).unwrap() * 10 + ones
+                    ),
+                    None => write!(f, "{display_string} {tens}"),
+                };
+            }
+            Some(x) => panic!("Invalid condition argument encountered: {x}"),
+            None => return write!(f, "{display_string}"),
+        };
+        display_string.push_str(condition_arg_1);
+
+        let condition_arg_2 = match chars.next() {
+            Some(num) if num.is_numeric() => format!(" {num}"),
+            Some(x) => panic!("Invalid condition argument encountered: {x}"),
+            None => return write!(f, "{display_string}"),
+        };
+        display_string.push_str(&condition_arg_2);
+
+        write!(f, "{display_string}")
+    }
+}
+
+fn to_digit(ch: char) -> Option<u8> {
+    Some(ch.to_digit(10)? as u8)
+}
+
+fn is_direction(ch: char) -> bool {
+    Direction::try_from(ch).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use zen_atc::{Command, Object, Point};
+
+    use super::*;
+
+    #[test]
+    fn command_display_matches_writer() {
+        let objects = [
+            Object::Airport {
+                position: Point(1, 1),
+                direction: Direction::East,
+                label: Some(1),
+            },
+            Object::Beacon {
+                position: Point(2, 2),
+                label: Some(2),
+            },
+            Object::Exit {
+                position: Point(0, 3),
+                direction: Direction::West,
+                label: Some(3),
+            },
+        ];
+
+        let inputs = [
+            "ate", "atq", "atl", "atR", "atLab2", "ahr", "ahlaa1", "aga1", "agb2", "age3",
+            "agb2i05", "ac3", "ad0", "am", "au", "ai", "ateab2", "ac9aa1", "adi12", "ami05",
+            "aui30",
+        ];
+        for input in inputs {
+            let mut writer = CommandWriter::new();
+            for ch in input.chars() {
+                writer.push(ch);
+            }
+
+            let written = writer.to_string();
+            let (plane, command) = Command::parse(writer.input(), &objects, 0)
+                .unwrap_or_else(|e| panic!("{input} should parse: {e}"));
+
+            assert_eq!(written, format!("{plane}: {command}"), "input: {input}");
+        }
+    }
+}
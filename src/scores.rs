@@ -0,0 +1,187 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::paths;
+
+const SCORE_FILE: &str = "scores.jsonl";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Score {
+    pub player: String,
+    pub map: String,
+    pub planes_handled: u32,
+    pub ticks: u32,
+    pub reason: String,
+    pub timestamp: u64,
+}
+
+impl Score {
+    pub fn new(
+        player: String,
+        map: String,
+        planes_handled: u32,
+        ticks: u32,
+        reason: String,
+    ) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+
+        Self {
+            player,
+            map,
+            planes_handled,
+            ticks,
+            reason,
+            timestamp,
+        }
+    }
+
+    // Civil date from days since the epoch, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    pub fn date(&self) -> String {
+        let days = (self.timestamp / 86400) as i64 + 719468;
+        let era = days.div_euclid(146097);
+        let day_of_era = days.rem_euclid(146097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+        format!("{year:04}-{month:02}-{day:02}")
+    }
+}
+
+fn score_path() -> io::Result<PathBuf> {
+    paths::data_dir()
+        .map(|d| d.join(SCORE_FILE))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory available"))
+}
+
+pub fn record(score: &Score) -> io::Result<()> {
+    let path = score_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut line = serde_json::to_string(score)?;
+    line.push('\n');
+
+    // Held until the file is dropped, so concurrent games append whole lines
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.lock()?;
+    file.write_all(line.as_bytes())?;
+
+    Ok(())
+}
+
+pub fn load() -> io::Result<Vec<Score>> {
+    let file = match File::open(score_path()?) {
+        Ok(f) => f,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    file.lock_shared()?;
+
+    let mut scores = Vec::new();
+    for line in BufReader::new(&file).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        scores.push(serde_json::from_str(&line)?);
+    }
+
+    Ok(scores)
+}
+
+pub fn ranked(map: Option<&str>) -> io::Result<Vec<Score>> {
+    Ok(rank(load()?, map))
+}
+
+// Most planes handled first, with the quicker game winning a tie
+fn rank(scores: Vec<Score>, map: Option<&str>) -> Vec<Score> {
+    let mut scores: Vec<Score> = scores
+        .into_iter()
+        .filter(|s| map.is_none_or(|m| s.map == m))
+        .collect();
+    scores.sort_by(|a, b| {
+        b.planes_handled
+            .cmp(&a.planes_handled)
+            .then(a.ticks.cmp(&b.ticks))
+    });
+
+    scores
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(player: &str, map: &str, planes_handled: u32, ticks: u32, timestamp: u64) -> Score {
+        Score {
+            player: player.to_owned(),
+            map: map.to_owned(),
+            planes_handled,
+            ticks,
+            reason: "Quit".to_owned(),
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn date_from_timestamp() {
+        let cases = [
+            (0, "1970-01-01"),
+            (946598400, "1999-12-31"),
+            (951782400, "2000-02-29"),
+            (951868800 - 1, "2000-02-29"),
+            (951868800, "2000-03-01"),
+            (1709164800, "2024-02-29"),
+            (4107542400, "2100-03-01"),
+        ];
+
+        for (timestamp, date) in cases {
+            assert_eq!(score("a", "m", 0, 0, timestamp).date(), date, "{timestamp}");
+        }
+    }
+
+    #[test]
+    fn ranking_breaks_ties_on_ticks() {
+        let scores = vec![
+            score("slow", "default", 5, 300, 0),
+            score("few", "default", 2, 50, 0),
+            score("fast", "default", 5, 200, 0),
+            score("most", "default", 8, 900, 0),
+            score("other", "big", 20, 100, 0),
+        ];
+
+        let ranked: Vec<String> = rank(scores, Some("default"))
+            .into_iter()
+            .map(|s| s.player)
+            .collect();
+        assert_eq!(ranked, ["most", "fast", "slow", "few"]);
+    }
+
+    #[test]
+    fn ranking_without_map_keeps_every_score() {
+        let scores = vec![
+            score("a", "default", 1, 10, 0),
+            score("b", "big", 3, 10, 0),
+            score("c", "default", 3, 5, 0),
+        ];
+
+        let ranked: Vec<String> = rank(scores, None).into_iter().map(|s| s.player).collect();
+        assert_eq!(ranked, ["c", "b", "a"]);
+    }
+}
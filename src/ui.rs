@@ -1,13 +1,43 @@
 use ratatui::{
-    layout::{Alignment, Constraint, Direction as LDirection, Layout},
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Direction as LDirection, Layout, Rect},
+    style::{Modifier, Style},
     text::Line,
-    widgets::{Block, Borders, List, ListItem, Paragraph},
+    widgets::{Block, Borders, List, ListItem, Paragraph, Widget},
     Frame,
 };
 
-use crate::game::App;
+use zen_atc::{App, Direction, MarkStatus, Object, Point};
 
-pub fn ui(frame: &mut Frame, app: &App) {
+use crate::prompt::Prompt;
+
+pub fn ui(frame: &mut Frame, app: &App, prompt: &Prompt, header: String, paused: bool) {
+    let command_text = match (app.game_over(), prompt.error()) {
+        (Some(game_over), _) => format!("Game over: {game_over} (seed {})", app.seed()),
+        (None, Some(e)) => format!("Invalid command: {e}"),
+        (None, None) => prompt.text(),
+    };
+
+    draw(frame, app, header, paused, "Command", command_text);
+}
+
+pub fn replay_ui(frame: &mut Frame, app: &App, status: String) {
+    let status = match app.game_over() {
+        Some(game_over) => format!("Game over: {game_over} | {status}"),
+        None => status,
+    };
+
+    draw(frame, app, String::new(), false, "Replay", status);
+}
+
+fn draw(
+    frame: &mut Frame,
+    app: &App,
+    header: String,
+    hide_radar: bool,
+    command_title: &str,
+    command_text: String,
+) {
     let frame_width = frame.size().width;
     // let (map_width, map_height) = app.dimensions();
     let (map_width, map_height) = {
@@ -45,25 +75,135 @@ pub fn ui(frame: &mut Frame, app: &App) {
     let radar_block = make_block("Radar");
     let command_list_block = make_block("Commands");
     let plane_list_block = make_block("Planes");
-    let command_block = make_block("Command");
+    let command_block = make_block(command_title);
 
-    let command_writer = Paragraph::new(app.cur_command()).block(command_block);
+    let command_writer = Paragraph::new(command_text).block(command_block);
     let command_list_items: Vec<ListItem> = app
         .commands()
         .iter()
         .map(|c| ListItem::new(Line::from(format!("[{}] {}: {}", c.0.tick(), c.1, c.0))))
         .collect();
     let command_list = List::new(command_list_items).block(command_list_block);
+    let plane_list_items: Vec<ListItem> = app
+        .planes()
+        .iter()
+        .filter_map(|plane| {
+            let style = match plane.mark_status() {
+                MarkStatus::Marked => Style::default(),
+                MarkStatus::Unmarked => Style::default().add_modifier(Modifier::DIM),
+                MarkStatus::Ignored => return None,
+            };
+
+            let mut strip = format!(
+                "{}{} {} fuel:{} {}",
+                plane.label(),
+                plane.altitude(),
+                plane.class(),
+                plane.fuel(),
+                plane.destination()
+            );
+            if let Some(command) = plane.commands().first() {
+                strip.push_str(&format!(": {command}"));
+            }
+
+            Some(ListItem::new(Line::from(strip)).style(style))
+        })
+        .collect();
+    let plane_list = List::new(plane_list_items).block(plane_list_block);
+
+    let header = Paragraph::new(header).alignment(Alignment::Center);
 
-    frame.render_widget(radar_block, upper_chunks[1]);
+    frame.render_widget(header, chunks[0]);
+    if hide_radar {
+        // Keep the map out of sight so a pause can't be used to plan ahead
+        let paused = Paragraph::new("Paused")
+            .alignment(Alignment::Center)
+            .block(radar_block);
+        frame.render_widget(paused, upper_chunks[1]);
+    } else {
+        frame.render_widget(radar_block, upper_chunks[1]);
+        frame.render_widget(Radar { app }, upper_chunks[1]);
+    }
     frame.render_widget(command_list, lower_chunks[0]);
-    frame.render_widget(plane_list_block, lower_chunks[1]);
+    frame.render_widget(plane_list, lower_chunks[1]);
     frame.render_widget(command_writer, chunks[4]);
 }
 
-fn make_block(title: &str) -> Block {
+fn make_block(title: &str) -> Block<'_> {
     Block::default()
         .borders(Borders::ALL)
         .title(title)
         .title_alignment(Alignment::Center)
 }
+
+struct Radar<'a> {
+    app: &'a App,
+}
+
+impl Radar<'_> {
+    // Map cells sit on every other column and row of the radar, with the
+    // border of the radar block standing in for the edge of the map
+    fn cell(&self, area: Rect, point: &Point) -> (u16, u16) {
+        let Point(x, y) = point;
+        let column = (2 * x).saturating_sub(1).min(area.width.saturating_sub(1));
+        let row = (2 * y).saturating_sub(1).min(area.height.saturating_sub(1));
+
+        (area.x + column, area.y + row)
+    }
+
+    fn draw(&self, area: Rect, buf: &mut Buffer, point: &Point, text: &str, style: Style) {
+        let (x, y) = self.cell(area, point);
+
+        for (k, ch) in text.chars().enumerate() {
+            let x = x + k as u16;
+            if x >= area.right() || y >= area.bottom() {
+                break;
+            }
+
+            buf.get_mut(x, y).set_char(ch).set_style(style);
+        }
+    }
+}
+
+impl Widget for Radar<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let map = self.app.map();
+
+        for line in map.lines() {
+            for point in line.points() {
+                self.draw(area, buf, &point, ".", Style::default());
+            }
+        }
+
+        for object in map.objects() {
+            let label = object.label().map_or("?".to_owned(), u8::to_string);
+            let text = match object {
+                Object::Airport { direction, .. } => format!("{}{label}", airport_glyph(direction)),
+                Object::Beacon { .. } => format!("*{label}"),
+                Object::Exit { .. } => label,
+            };
+
+            self.draw(area, buf, object.position(), &text, Style::default());
+        }
+
+        for plane in self.app.planes() {
+            let text = format!("{}{}", plane.label(), plane.altitude());
+            let style = Style::default().add_modifier(Modifier::BOLD);
+
+            self.draw(area, buf, plane.position(), &text, style);
+        }
+    }
+}
+
+fn airport_glyph(direction: &Direction) -> char {
+    match direction {
+        Direction::North => '^',
+        Direction::Northeast => '/',
+        Direction::East => '>',
+        Direction::Southeast => '\\',
+        Direction::South => 'v',
+        Direction::Southwest => '/',
+        Direction::West => '<',
+        Direction::Northwest => '\\',
+    }
+}